@@ -1,33 +1,77 @@
+pub mod arguments;
+mod clipboard;
 mod command;
+mod commandbar;
+mod config;
 mod documentstatus;
 mod fileinfo;
 mod messagebar;
 mod statusbar;
+mod syntax;
 mod terminal;
+mod theme;
 mod view;
-use command::Command;
+use arguments::{parse_line_column, split_file_location, Arguments, STDIN_FILE_NAME};
+use clipboard::Clipboard;
+use command::{ClipboardCommand, Command, EditCommand, FileCommand};
+use commandbar::CommandBar;
+use config::Config;
 use crossterm::event::{poll, read, Event, KeyEvent, KeyEventKind};
 use messagebar::MessageBar;
 use statusbar::StatusBar;
-use std::env;
-use std::io::Error;
+use std::io::{self, Error, IsTerminal};
+use std::process::ExitCode;
 use std::time::Duration;
-use terminal::Terminal;
+use syntax::Syntax;
+use terminal::{Size, Terminal};
+use theme::Theme;
 use view::View;
 
 pub const NAME: &str = env!("CARGO_PKG_NAME");
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum PromptType {
+    #[default]
+    None,
+    CreateFile,
+    RenameFile,
+    RemoveFile,
+    GoToLine,
+    SaveAs,
+}
+
 pub struct Editor {
     should_quit: bool,
     view: View,
     status_bar: StatusBar,
     message_bar: MessageBar,
+    command_bar: CommandBar,
+    clipboard: Clipboard,
+    prompt_type: PromptType,
     title: String,
+    files: Vec<String>,
+    current_file: usize,
+    stdin_contents: Option<String>,
+    fail_if_unsaved: bool,
+    has_saved: bool,
 }
 
 impl Editor {
-    pub fn new() -> Result<Self, Error> {
+    pub fn new(arguments: Arguments) -> Result<Self, Error> {
+        let config = Config::load(arguments.config_path.as_deref())?;
+        let syntaxes = Syntax::load_all()?;
+        let theme = Theme::load(&config.theme)?;
+        let mut files = arguments.files;
+        if files.is_empty() && !io::stdin().is_terminal() {
+            files.push(STDIN_FILE_NAME.to_string());
+        }
+        //read piped input before raw mode is entered; key events are then read from the terminal
+        let stdin_contents = if files.iter().any(|file| file == STDIN_FILE_NAME) {
+            Some(io::read_to_string(io::stdin())?)
+        } else {
+            None
+        };
         let current_hook = std::panic::take_hook();
         std::panic::set_hook(Box::new(move |panic_info| {
             let _ = Terminal::terminate();
@@ -39,23 +83,85 @@ impl Editor {
             view: View::new(2),
             status_bar: StatusBar::new(1),
             message_bar: MessageBar::new(),
+            command_bar: CommandBar::new(),
+            clipboard: Clipboard::new(&config),
+            prompt_type: PromptType::default(),
             title: String::new(),
+            files,
+            current_file: 0,
+            stdin_contents,
+            fail_if_unsaved: arguments.fail_if_unsaved,
+            has_saved: false,
         };
+        editor
+            .view
+            .set_readonly(arguments.readonly || config.readonly);
+        editor.view.set_syntaxes(syntaxes);
+        editor.view.set_theme(theme);
+        editor.view.set_line_numbers(config.line_numbers);
+        editor.view.set_tab_width(config.tab_width);
+        editor.view.set_soft_wrap(config.soft_wrap);
+        editor.view.set_indentation_settings(config.indentation);
+        editor.status_bar.set_style(theme.status_bar);
+        editor.message_bar.set_style(theme.message_bar);
+        editor.command_bar.set_style(theme.message_bar);
         editor
             .message_bar
             .update_msg("HELP: Ctrl-S = save | Ctrl-Q = quit");
-        let args: Vec<String> = env::args().collect();
-        if let Some(file_name) = args.get(1) {
-            if editor.view.load(file_name).is_err() {
-                editor
-                    .message_bar
-                    .update_msg("ERR: Could not open file: {file_name}");
-            }
-        }
+        editor.open_file(0, arguments.start_location);
         editor.refresh_status();
         Ok(editor)
     }
 
+    //opens the file argument at the given index, optionally at a location
+    fn open_file(&mut self, index: usize, start_location: Option<(usize, usize)>) {
+        let Some(file_arg) = self.files.get(index).cloned() else {
+            return;
+        };
+        let file_location = if file_arg == STDIN_FILE_NAME {
+            self.view
+                .load_text(self.stdin_contents.as_deref().unwrap_or_default());
+            None
+        } else {
+            let (file_name, file_location) = split_file_location(&file_arg);
+            if self.view.load(file_name).is_err() {
+                self.message_bar
+                    .update_msg(&format!("ERR: Could not open file: {file_name}"));
+                return;
+            }
+            file_location
+        };
+        //the file only becomes the current one once loaded, as a failed load keeps the old buffer
+        self.current_file = index;
+        if let Some((line_index, grapheme_index)) = file_location.or(start_location) {
+            self.view.go_to(line_index, grapheme_index);
+        }
+        if self.files.len() > 1 {
+            let file_number = index.saturating_add(1);
+            self.message_bar.update_msg(&format!(
+                "File {file_number} of {}: {file_arg}",
+                self.files.len()
+            ));
+        }
+    }
+
+    fn switch_file(&mut self, forward: bool) {
+        let index = if forward {
+            self.current_file.saturating_add(1)
+        } else {
+            self.current_file.saturating_sub(1)
+        };
+        if index == self.current_file || index >= self.files.len() {
+            self.message_bar.update_msg("No more files");
+        } else if self.view.is_modified() {
+            self.message_bar
+                .update_msg("Unsaved changes. Save with Ctrl-S before switching files");
+        } else {
+            self.open_file(index, None);
+            self.refresh_status();
+        }
+    }
+
     pub fn refresh_status(&mut self) {
         let status = self.view.get_current_document_status();
         let title = format!("{} - {NAME}", status.file_name);
@@ -72,7 +178,7 @@ impl Editor {
             self.refresh_screen();
             if self.should_quit {
                 break;
-            };
+            }
             if poll(Duration::from_millis(100)).unwrap() {
                 match read() {
                     Ok(event) => self.evaluate_event(event),
@@ -91,44 +197,261 @@ impl Editor {
     fn evaluate_event(&mut self, event: Event) {
         let should_process = match &event {
             Event::Key(KeyEvent { kind, .. }) => kind == &KeyEventKind::Press,
-            Event::Resize(_, _) => true,
+            Event::Resize(_, _) | Event::Paste(_) | Event::Mouse(_) => true,
             _ => false,
         };
         if should_process {
             if let Ok(command) = Command::try_from(event) {
-                match command {
-                    Command::Quit => {
-                        self.should_quit = true;
-                    }
-                    Command::Save => {
-                        if self.view.save().is_ok() {
-                            self.message_bar.update_msg("File saved successfully");
-                        } else {
-                            self.message_bar.update_msg("Error writing file!");
-                        }
-                    }
-                    Command::Edit(edit_command) => {
-                        self.view.handle_edit_command(edit_command);
-                    }
-                    Command::Move(direction) => self.view.move_text_location(direction),
-                    Command::Resize(size) => {
-                        self.view.resize(size);
-                        self.status_bar.resize(size);
-                        self.message_bar.resize(size);
+                if self.prompt_type == PromptType::None {
+                    self.process_command(command);
+                } else {
+                    self.process_command_during_prompt(command);
+                }
+            }
+        }
+    }
+
+    fn process_command(&mut self, command: Command) {
+        match command {
+            Command::Quit => {
+                self.should_quit = true;
+            }
+            Command::Save | Command::Paste(_) if self.view.is_readonly() => {
+                self.message_bar.update_msg("File is read-only");
+            }
+            Command::Save if !self.view.has_file_name() => {
+                self.start_prompt(PromptType::SaveAs, "Save as: ", "");
+            }
+            Command::Save => {
+                if self.view.save().is_ok() {
+                    self.has_saved = true;
+                    self.message_bar.update_msg("File saved successfully");
+                } else {
+                    self.message_bar.update_msg("Error writing file!");
+                }
+            }
+            Command::Edit(EditCommand::Enter) if self.view.is_browsing() => {
+                if self.view.open_selected_entry().is_err() {
+                    self.message_bar.update_msg("ERR: Could not open entry");
+                }
+                self.refresh_status();
+            }
+            Command::Clipboard(clipboard_command) => {
+                self.process_clipboard_command(clipboard_command);
+            }
+            Command::Paste(text) => self.view.paste(&text, false),
+            Command::Edit(_) | Command::Line(_) if self.view.is_readonly() => {
+                self.message_bar.update_msg("File is read-only");
+            }
+            Command::Edit(edit_command) => {
+                self.view.handle_edit_command(edit_command);
+            }
+            Command::Line(line_command) => self.view.handle_line_command(line_command),
+            Command::Move(direction) => self.view.move_text_location(direction),
+            Command::Select(direction) => self.view.select_text_location(direction),
+            Command::Mouse(mouse_command) => self.view.handle_mouse_command(mouse_command),
+            Command::NextFile => self.switch_file(true),
+            Command::PreviousFile => self.switch_file(false),
+            Command::File(file_command) => self.start_file_prompt(file_command),
+            Command::GoToLine => self.start_prompt(PromptType::GoToLine, "Go to line: ", ""),
+            Command::ToggleSoftWrap => {
+                let message = if self.view.toggle_soft_wrap() {
+                    "Soft wrap on"
+                } else {
+                    "Soft wrap off"
+                };
+                self.message_bar.update_msg(message);
+            }
+            Command::Resize(size) => self.resize(size),
+            Command::Dismiss => self.view.clear_selection(),
+        }
+    }
+
+    fn process_command_during_prompt(&mut self, command: Command) {
+        match command {
+            Command::Quit => {
+                self.should_quit = true;
+            }
+            Command::Dismiss => {
+                self.end_prompt();
+                self.message_bar.update_msg("Aborted.");
+            }
+            Command::Edit(EditCommand::Enter) => {
+                let value = self.command_bar.value();
+                let prompt_type = self.prompt_type;
+                self.end_prompt();
+                self.submit_prompt(prompt_type, &value);
+            }
+            Command::Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            Command::Paste(text) => {
+                for c in text.chars().take_while(|&c| c != '\n') {
+                    self.command_bar.handle_edit_command(EditCommand::Insert(c));
+                }
+            }
+            Command::Resize(size) => self.resize(size),
+            Command::Move(_)
+            | Command::Line(_)
+            | Command::Select(_)
+            | Command::Mouse(_)
+            | Command::Clipboard(_)
+            | Command::File(_)
+            | Command::GoToLine
+            | Command::ToggleSoftWrap
+            | Command::NextFile
+            | Command::PreviousFile
+            | Command::Save => {}
+        }
+    }
+
+    fn process_clipboard_command(&mut self, clipboard_command: ClipboardCommand) {
+        if self.view.is_readonly() && !matches!(clipboard_command, ClipboardCommand::Copy) {
+            self.message_bar.update_msg("File is read-only");
+            return;
+        }
+        match clipboard_command {
+            ClipboardCommand::Cut | ClipboardCommand::Copy => {
+                let copied = match self.view.get_selected_text() {
+                    Some(text) => Some((text, false)),
+                    None => self.view.get_current_line_text().map(|text| (text, true)),
+                };
+                if let Some((text, is_whole_line)) = copied {
+                    self.clipboard.set(text, is_whole_line);
+                }
+                if matches!(clipboard_command, ClipboardCommand::Cut) {
+                    self.view.cut();
+                }
+            }
+            ClipboardCommand::Paste => match self.clipboard.get() {
+                Some((text, is_whole_line)) => self.view.paste(&text, is_whole_line),
+                None => self.message_bar.update_msg("Clipboard is empty"),
+            },
+        }
+    }
+
+    fn start_file_prompt(&mut self, file_command: FileCommand) {
+        if !self.view.is_browsing() {
+            self.message_bar
+                .update_msg("File commands are only available in the directory browser");
+            return;
+        }
+        if self.view.is_readonly() {
+            self.message_bar.update_msg("File is read-only");
+            return;
+        }
+        let entry_name = self.view.get_selected_entry_name();
+        match (file_command, entry_name) {
+            (FileCommand::Create, _) => {
+                self.start_prompt(PromptType::CreateFile, "New file: ", "");
+            }
+            (FileCommand::Rename, Some(name)) => {
+                self.start_prompt(PromptType::RenameFile, "Rename to: ", &name);
+            }
+            (FileCommand::Remove, Some(name)) => {
+                let prompt = format!("Delete {name}? (y/n): ");
+                self.start_prompt(PromptType::RemoveFile, &prompt, "");
+            }
+            (FileCommand::Rename | FileCommand::Remove, None) => {
+                self.message_bar.update_msg("No entry selected");
+            }
+        }
+    }
+
+    fn start_prompt(&mut self, prompt_type: PromptType, prompt: &str, value: &str) {
+        self.prompt_type = prompt_type;
+        self.command_bar.set_prompt(prompt, value);
+    }
+
+    fn end_prompt(&mut self) {
+        self.prompt_type = PromptType::None;
+        self.message_bar.set_needs_redraw();
+    }
+
+    fn submit_prompt(&mut self, prompt_type: PromptType, value: &str) {
+        let result = match prompt_type {
+            PromptType::None => return,
+            PromptType::GoToLine => {
+                match parse_line_column(value) {
+                    Some((line_index, grapheme_index)) => {
+                        self.view.go_to(line_index, grapheme_index);
                     }
+                    None => self.message_bar.update_msg("Expected line or line:column"),
                 }
+                return;
+            }
+            PromptType::SaveAs if !value.is_empty() => {
+                self.save_as(value);
+                return;
             }
+            PromptType::CreateFile if !value.is_empty() => self
+                .view
+                .create_file(value)
+                .map(|()| format!("Created {value}")),
+            PromptType::RenameFile if !value.is_empty() => self
+                .view
+                .rename_selected_entry(value)
+                .map(|()| format!("Renamed to {value}")),
+            PromptType::RemoveFile if value.eq_ignore_ascii_case("y") => self
+                .view
+                .remove_selected_entry()
+                .map(|()| "Deleted.".to_string()),
+            PromptType::CreateFile
+            | PromptType::RenameFile
+            | PromptType::RemoveFile
+            | PromptType::SaveAs => Ok("Aborted.".to_string()),
+        };
+        match result {
+            Ok(msg) => self.message_bar.update_msg(&msg),
+            Err(err) => self.message_bar.update_msg(&format!("ERR: {err}")),
+        }
+    }
+
+    fn save_as(&mut self, file_name: &str) {
+        if self.view.save_as(file_name).is_err() {
+            self.message_bar.update_msg("Error writing file!");
+            return;
+        }
+        if let Some(file) = self.files.get_mut(self.current_file) {
+            *file = file_name.to_string();
+        }
+        self.has_saved = true;
+        self.message_bar.update_msg("File saved successfully");
+        self.refresh_status();
+    }
+
+    //tools using hecto as $EDITOR can request a failure status when nothing was saved
+    pub fn exit_code(&self) -> ExitCode {
+        if self.fail_if_unsaved && (!self.has_saved || self.view.is_modified()) {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
         }
     }
 
+    fn resize(&mut self, size: Size) {
+        self.view.resize(size);
+        self.status_bar.resize(size);
+        self.message_bar.resize(size);
+        self.command_bar.resize(size);
+    }
+
     fn refresh_screen(&mut self) {
         let _ = Terminal::hide_caret();
-        let _ = self.view.render();
-        let _ = self.status_bar.render();
-        let _ = self.message_bar.render();
-        //update the position of the caret
-        let _ = Terminal::move_caret_to(self.view.get_caret_position());
-        let _ = Terminal::show_caret();
+        self.view.render();
+        self.status_bar.render();
+        let caret_position = if self.prompt_type == PromptType::None {
+            self.message_bar.render();
+            //the caret stays hidden while the mouse wheel has scrolled it out of view
+            self.view
+                .is_caret_visible()
+                .then(|| self.view.get_caret_position())
+        } else {
+            self.command_bar.render();
+            Some(self.command_bar.get_caret_position())
+        };
+        if let Some(caret_position) = caret_position {
+            let _ = Terminal::move_caret_to(caret_position);
+            let _ = Terminal::show_caret();
+        }
         let _ = Terminal::buffer_flush();
     }
 }
@@ -136,7 +459,8 @@ impl Editor {
 impl Drop for Editor {
     fn drop(&mut self) {
         let _ = Terminal::terminate();
-        if self.should_quit {
+        //tools capturing stdout must not receive the farewell
+        if self.should_quit && io::stdout().is_terminal() {
             let _ = Terminal::print("Goodbye.\r\n");
         }
     }
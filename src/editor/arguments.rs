@@ -0,0 +1,125 @@
+use super::{NAME, VERSION};
+use std::path::{Path, PathBuf};
+
+pub const STDIN_FILE_NAME: &str = "-";
+
+pub enum Action {
+    Edit(Arguments),
+    Help,
+    Version,
+}
+
+#[derive(Default)]
+pub struct Arguments {
+    pub files: Vec<String>,
+    pub readonly: bool,
+    pub fail_if_unsaved: bool,
+    pub config_path: Option<PathBuf>,
+    pub start_location: Option<(usize, usize)>,
+}
+
+impl Arguments {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Action, String> {
+        let mut arguments = Self::default();
+        let mut args = args.into_iter();
+        let mut options_ended = false;
+        while let Some(arg) = args.next() {
+            if options_ended || arg == STDIN_FILE_NAME {
+                arguments.files.push(arg);
+                continue;
+            }
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Action::Help),
+                "-V" | "--version" => return Ok(Action::Version),
+                "-R" | "--readonly" => arguments.readonly = true,
+                "--fail-if-unsaved" => arguments.fail_if_unsaved = true,
+                "-c" | "--config" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {arg}"))?;
+                    arguments.config_path = Some(PathBuf::from(path));
+                }
+                "--" => options_ended = true,
+                _ if arg.starts_with('+') => {
+                    let location = parse_line_column(&arg[1..])
+                        .ok_or_else(|| format!("invalid line number: {arg}"))?;
+                    arguments.start_location = Some(location);
+                }
+                _ if arg.starts_with('-') => {
+                    if let Some(path) = arg.strip_prefix("--config=") {
+                        arguments.config_path = Some(PathBuf::from(path));
+                    } else {
+                        return Err(format!("unknown option: {arg}"));
+                    }
+                }
+                _ => arguments.files.push(arg),
+            }
+        }
+        if arguments
+            .files
+            .iter()
+            .filter(|file| file.as_str() == STDIN_FILE_NAME)
+            .count()
+            > 1
+        {
+            return Err(format!("{STDIN_FILE_NAME} can only be given once"));
+        }
+        Ok(Action::Edit(arguments))
+    }
+}
+
+pub fn version() -> String {
+    format!("{NAME} {VERSION}")
+}
+
+pub fn usage() -> String {
+    format!(
+        "Usage: {NAME} [OPTIONS] [+LINE[:COLUMN]] [FILE[:LINE[:COLUMN]]...]
+
+Opens each FILE for editing. A directory is opened in the file browser,
+and - reads the document from standard input.
+
+Options:
+  -R, --readonly       Open files without allowing changes
+      --fail-if-unsaved
+                       Exit with status 1 unless the file was saved, e.g. to
+                       abort a git commit when hecto is $GIT_EDITOR
+  -c, --config <PATH>  Read settings from PATH instead of the default config
+  -h, --help           Print this help and exit
+  -V, --version        Print the version and exit"
+    )
+}
+
+//parses "line" or "line:column", both 1-based, into a 0-based line and grapheme index
+pub fn parse_line_column(value: &str) -> Option<(usize, usize)> {
+    let (line, column) = match value.trim().split_once(':') {
+        Some((line, column)) => (line, Some(column)),
+        None => (value.trim(), None),
+    };
+    let line = line.parse::<usize>().ok()?;
+    let column = match column {
+        Some(column) => column.parse::<usize>().ok()?,
+        None => 1,
+    };
+    Some((line.saturating_sub(1), column.saturating_sub(1)))
+}
+
+//splits "file.rs:120:5" or "file.rs:120" into the file name and location,
+//unless a file with the full name exists
+pub fn split_file_location(arg: &str) -> (&str, Option<(usize, usize)>) {
+    if Path::new(arg).exists() {
+        return (arg, None);
+    }
+    let Some((rest, last)) = arg.rsplit_once(':') else {
+        return (arg, None);
+    };
+    if let Some((file_name, line)) = rest.rsplit_once(':') {
+        if let Some(location) = parse_line_column(&format!("{line}:{last}")) {
+            return (file_name, Some(location));
+        }
+    }
+    match parse_line_column(last) {
+        Some(location) => (rest, Some(location)),
+        None => (arg, None),
+    }
+}
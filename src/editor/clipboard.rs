@@ -0,0 +1,135 @@
+use super::config::Config;
+use super::terminal::Terminal;
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+//commands copying from stdin to the system clipboard and pasting from it to stdout
+struct Helper {
+    copy: &'static [&'static str],
+    paste: &'static [&'static str],
+}
+
+const WL_CLIPBOARD: Helper = Helper {
+    copy: &["wl-copy"],
+    paste: &["wl-paste", "--no-newline"],
+};
+const XCLIP: Helper = Helper {
+    copy: &["xclip", "-selection", "clipboard", "-in"],
+    paste: &["xclip", "-selection", "clipboard", "-out"],
+};
+const XSEL: Helper = Helper {
+    copy: &["xsel", "--clipboard", "--input"],
+    paste: &["xsel", "--clipboard", "--output"],
+};
+const PBCOPY: Helper = Helper {
+    copy: &["pbcopy"],
+    paste: &["pbpaste"],
+};
+
+//text copied or cut from the view, kept until the next copy or cut and mirrored to the
+//system clipboard when a helper command or OSC 52 is available
+#[derive(Default)]
+pub struct Clipboard {
+    text: String,
+    is_whole_line: bool,
+    use_osc52: bool,
+    helper: Option<Helper>,
+}
+
+impl Clipboard {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            use_osc52: config.clipboard_osc52,
+            helper: Self::find_helper(&config.clipboard_helper),
+            ..Self::default()
+        }
+    }
+
+    //"auto" picks the first helper available in this session, "none" disables helpers
+    fn find_helper(name: &str) -> Option<Helper> {
+        match name {
+            "wl-clipboard" => Some(WL_CLIPBOARD),
+            "xclip" => Some(XCLIP),
+            "xsel" => Some(XSEL),
+            "pbcopy" => Some(PBCOPY),
+            "auto" => {
+                let has_wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+                let has_x11 = env::var_os("DISPLAY").is_some();
+                [
+                    (WL_CLIPBOARD, has_wayland),
+                    (XCLIP, has_x11),
+                    (XSEL, has_x11),
+                    (PBCOPY, cfg!(target_os = "macos")),
+                ]
+                .into_iter()
+                .find(|(helper, is_usable)| {
+                    *is_usable && is_on_path(helper.copy[0]) && is_on_path(helper.paste[0])
+                })
+                .map(|(helper, _)| helper)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, text: String, is_whole_line: bool) {
+        if let Some(helper) = &self.helper {
+            if copy_with(helper.copy, &text).is_none() {
+                self.helper = None;
+            }
+        }
+        if self.use_osc52 {
+            let _ = Terminal::set_clipboard(&text);
+        }
+        self.text = text;
+        self.is_whole_line = is_whole_line;
+    }
+
+    //prefers the system clipboard, so text copied in other programs can be pasted
+    pub fn get(&self) -> Option<(String, bool)> {
+        let system_text = self
+            .helper
+            .as_ref()
+            .and_then(|helper| paste_with(helper.paste))
+            .filter(|text| !text.is_empty() && *text != self.text);
+        if let Some(text) = system_text {
+            return Some((text, false));
+        }
+        if self.text.is_empty() {
+            return None;
+        }
+        Some((self.text.clone(), self.is_whole_line))
+    }
+}
+
+fn is_on_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+fn copy_with(command: &[&str], text: &str) -> Option<()> {
+    let (program, args) = command.split_first()?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(text.as_bytes()).ok()?;
+    child.wait().ok()?.success().then_some(())
+}
+
+fn paste_with(command: &[&str]) -> Option<String> {
+    let (program, args) = command.split_first()?;
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
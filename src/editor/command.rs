@@ -1,5 +1,7 @@
-use super::terminal::Size;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use super::terminal::{Position, Size};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use std::convert::TryFrom;
 
 #[derive(Clone, Copy)]
@@ -12,6 +14,34 @@ pub enum Direction {
     PageDown,
     Home,
     End,
+    WordLeft,
+    WordRight,
+    ParagraphUp,
+    ParagraphDown,
+    DocumentStart,
+    DocumentEnd,
+}
+
+impl Direction {
+    fn from_key(code: KeyCode, modifiers: KeyModifiers) -> Option<Self> {
+        match (code, modifiers) {
+            (KeyCode::Up, KeyModifiers::NONE) => Some(Self::Up),
+            (KeyCode::Down, KeyModifiers::NONE) => Some(Self::Down),
+            (KeyCode::Left, KeyModifiers::NONE) => Some(Self::Left),
+            (KeyCode::Right, KeyModifiers::NONE) => Some(Self::Right),
+            (KeyCode::PageUp, KeyModifiers::NONE) => Some(Self::PageUp),
+            (KeyCode::PageDown, KeyModifiers::NONE) => Some(Self::PageDown),
+            (KeyCode::Home, KeyModifiers::NONE) => Some(Self::Home),
+            (KeyCode::End, KeyModifiers::NONE) => Some(Self::End),
+            (KeyCode::Left, KeyModifiers::CONTROL) => Some(Self::WordLeft),
+            (KeyCode::Right, KeyModifiers::CONTROL) => Some(Self::WordRight),
+            (KeyCode::Up, KeyModifiers::CONTROL) => Some(Self::ParagraphUp),
+            (KeyCode::Down, KeyModifiers::CONTROL) => Some(Self::ParagraphDown),
+            (KeyCode::Home, KeyModifiers::CONTROL) => Some(Self::DocumentStart),
+            (KeyCode::End, KeyModifiers::CONTROL) => Some(Self::DocumentEnd),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -19,12 +49,59 @@ pub enum EditCommand {
     Insert(char),
     Delete,
     Backspace,
+    DeleteWord,
+    BackspaceWord,
     Enter,
+    Indent,
+    Dedent,
+}
+
+#[derive(Clone, Copy)]
+pub enum LineCommand {
+    Duplicate,
+    MoveUp,
+    MoveDown,
+    Delete,
+    Join,
+}
+
+#[derive(Clone, Copy)]
+pub enum ClipboardCommand {
+    Cut,
+    Copy,
+    Paste,
+}
+
+#[derive(Clone, Copy)]
+pub enum MouseCommand {
+    Press(Position),
+    Drag(Position),
+    ScrollUp,
+    ScrollDown,
+}
+
+#[derive(Clone, Copy)]
+pub enum FileCommand {
+    Create,
+    Rename,
+    Remove,
 }
+
 pub enum Command {
     Edit(EditCommand),
+    Line(LineCommand),
     Move(Direction),
+    Select(Direction),
+    Clipboard(ClipboardCommand),
+    Paste(String),
+    Mouse(MouseCommand),
+    File(FileCommand),
     Resize(Size),
+    GoToLine,
+    ToggleSoftWrap,
+    NextFile,
+    PreviousFile,
+    Dismiss,
     Quit,
     Save,
 }
@@ -39,27 +116,84 @@ impl TryFrom<Event> for Command {
             }) => match (code, modifiers) {
                 (KeyCode::Char('q'), KeyModifiers::CONTROL) => Ok(Self::Quit),
                 (KeyCode::Char('s'), KeyModifiers::CONTROL) => Ok(Self::Save),
+                (KeyCode::Char('n'), KeyModifiers::CONTROL) => Ok(Self::File(FileCommand::Create)),
+                (KeyCode::Char('r'), KeyModifiers::CONTROL) => Ok(Self::File(FileCommand::Rename)),
+                (KeyCode::Char('d'), KeyModifiers::CONTROL) => Ok(Self::File(FileCommand::Remove)),
+                (KeyCode::Char('x'), KeyModifiers::CONTROL) => {
+                    Ok(Self::Clipboard(ClipboardCommand::Cut))
+                }
+                (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    Ok(Self::Clipboard(ClipboardCommand::Copy))
+                }
+                (KeyCode::Char('v'), KeyModifiers::CONTROL) => {
+                    Ok(Self::Clipboard(ClipboardCommand::Paste))
+                }
+                (KeyCode::Char('g'), KeyModifiers::CONTROL) => Ok(Self::GoToLine),
+                (KeyCode::Char('z'), KeyModifiers::ALT) => Ok(Self::ToggleSoftWrap),
+                (KeyCode::Char('k'), KeyModifiers::CONTROL) => Ok(Self::Line(LineCommand::Delete)),
+                (KeyCode::Char('j'), KeyModifiers::CONTROL) => Ok(Self::Line(LineCommand::Join)),
+                (KeyCode::Up, KeyModifiers::ALT) => Ok(Self::Line(LineCommand::MoveUp)),
+                (KeyCode::Down, KeyModifiers::ALT) => Ok(Self::Line(LineCommand::MoveDown)),
+                (KeyCode::Down, modifiers)
+                    if modifiers == KeyModifiers::ALT | KeyModifiers::SHIFT =>
+                {
+                    Ok(Self::Line(LineCommand::Duplicate))
+                }
+                (KeyCode::Esc, _) => Ok(Self::Dismiss),
                 (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
                     Ok(Self::Edit(EditCommand::Insert(c)))
                 }
-                (KeyCode::Backspace, _) => Ok(Self::Edit(EditCommand::Backspace)),
+                //without the kitty keyboard protocol, e.g. in xterm and VTE terminals, Ctrl+Backspace
+                //arrives as Ctrl-H, leaving Alt+Backspace to delete a word
+                (KeyCode::Backspace, KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                    Ok(Self::Edit(EditCommand::BackspaceWord))
+                }
+                (KeyCode::Delete, KeyModifiers::CONTROL) => Ok(Self::Edit(EditCommand::DeleteWord)),
+                //some terminals send Backspace as Ctrl-H, so it never deletes a word
+                (KeyCode::Backspace, _) | (KeyCode::Char('h'), KeyModifiers::CONTROL) => {
+                    Ok(Self::Edit(EditCommand::Backspace))
+                }
                 (KeyCode::Delete, _) => Ok(Self::Edit(EditCommand::Delete)),
-                (KeyCode::Tab, _) => Ok(Self::Edit(EditCommand::Insert('\t'))),
+                (KeyCode::Tab, _) => Ok(Self::Edit(EditCommand::Indent)),
+                (KeyCode::BackTab, _) => Ok(Self::Edit(EditCommand::Dedent)),
                 (KeyCode::Enter, _) => Ok(Self::Edit(EditCommand::Enter)),
-                (KeyCode::Up, _) => Ok(Self::Move(Direction::Up)),
-                (KeyCode::Down, _) => Ok(Self::Move(Direction::Down)),
-                (KeyCode::Left, _) => Ok(Self::Move(Direction::Left)),
-                (KeyCode::Right, _) => Ok(Self::Move(Direction::Right)),
-                (KeyCode::Home, _) => Ok(Self::Move(Direction::Home)),
-                (KeyCode::End, _) => Ok(Self::Move(Direction::End)),
-                (KeyCode::PageUp, _) => Ok(Self::Move(Direction::PageUp)),
-                (KeyCode::PageDown, _) => Ok(Self::Move(Direction::PageDown)),
-                _ => Err(format!("Key Code not supported: {code:?}")),
+                (KeyCode::PageDown, KeyModifiers::CONTROL) => Ok(Self::NextFile),
+                (KeyCode::PageUp, KeyModifiers::CONTROL) => Ok(Self::PreviousFile),
+                //holding Shift extends the selection with any movement
+                (code, modifiers) if modifiers.contains(KeyModifiers::SHIFT) => {
+                    Direction::from_key(code, modifiers.difference(KeyModifiers::SHIFT))
+                        .map(Self::Select)
+                        .ok_or_else(|| format!("Key Code not supported: {code:?}"))
+                }
+                (code, modifiers) => Direction::from_key(code, modifiers)
+                    .map(Self::Move)
+                    .ok_or_else(|| format!("Key Code not supported: {code:?}")),
             },
             Event::Resize(width_u16, height_u16) => Ok(Self::Resize(Size {
                 height: height_u16 as usize,
                 width: width_u16 as usize,
             })),
+            Event::Mouse(MouseEvent {
+                kind, column, row, ..
+            }) => {
+                let position = Position {
+                    col: column as usize,
+                    row: row as usize,
+                };
+                match kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        Ok(Self::Mouse(MouseCommand::Press(position)))
+                    }
+                    MouseEventKind::Drag(MouseButton::Left) => {
+                        Ok(Self::Mouse(MouseCommand::Drag(position)))
+                    }
+                    MouseEventKind::ScrollUp => Ok(Self::Mouse(MouseCommand::ScrollUp)),
+                    MouseEventKind::ScrollDown => Ok(Self::Mouse(MouseCommand::ScrollDown)),
+                    _ => Err(format!("Mouse event not supported: {kind:?}")),
+                }
+            }
+            //terminals send line breaks in pasted text as carriage returns
+            Event::Paste(text) => Ok(Self::Paste(text.replace("\r\n", "\n").replace('\r', "\n"))),
             _ => Err(format!("Event not supported: {event:?}")),
         }
     }
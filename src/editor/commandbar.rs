@@ -0,0 +1,87 @@
+use super::command::EditCommand;
+use super::terminal::{Position, Size, Terminal};
+use super::theme::Style;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Default)]
+pub struct CommandBar {
+    prompt: String,
+    value: String,
+    needs_redraw: bool,
+    size: Size,
+    style: Style,
+}
+
+impl CommandBar {
+    pub fn new() -> Self {
+        let size = Terminal::get_size().unwrap_or_default();
+        Self {
+            needs_redraw: true,
+            size,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+        self.needs_redraw = true;
+    }
+
+    pub fn set_prompt(&mut self, prompt: &str, value: &str) {
+        self.prompt = prompt.to_string();
+        self.value = value.to_string();
+        self.needs_redraw = true;
+    }
+
+    pub fn handle_edit_command(&mut self, command: EditCommand) {
+        match command {
+            EditCommand::Insert(c) => self.value.push(c),
+            EditCommand::Backspace => {
+                self.value.pop();
+            }
+            EditCommand::BackspaceWord => {
+                let trimmed_len = self.value.trim_end().len();
+                let word_start = self.value[..trimmed_len]
+                    .char_indices()
+                    .rfind(|(_, c)| c.is_whitespace())
+                    .map_or(0, |(index, c)| index.saturating_add(c.len_utf8()));
+                self.value.truncate(word_start);
+            }
+            EditCommand::Delete
+            | EditCommand::DeleteWord
+            | EditCommand::Enter
+            | EditCommand::Indent
+            | EditCommand::Dedent => {}
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn value(&self) -> String {
+        self.value.clone()
+    }
+
+    pub fn resize(&mut self, to: Size) {
+        self.size = to;
+        self.needs_redraw = true;
+    }
+
+    //the caret sits after the entered value, but never beyond the last column
+    pub fn get_caret_position(&self) -> Position {
+        let text_width = self.prompt.width().saturating_add(self.value.width());
+        Position {
+            col: text_width.min(self.size.width.saturating_sub(1)),
+            row: self.size.height.saturating_sub(1),
+        }
+    }
+
+    pub fn render(&mut self) {
+        if !self.needs_redraw {
+            return;
+        }
+        let text = format!("{}{}", self.prompt, self.value);
+        let result =
+            Terminal::print_styled_row(self.size.height.saturating_sub(1), &text, &self.style);
+        debug_assert!(result.is_ok(), "Failed to render command bar");
+        self.needs_redraw = false;
+    }
+}
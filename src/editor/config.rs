@@ -0,0 +1,435 @@
+use super::NAME;
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+const MAX_TAB_WIDTH: usize = 16;
+const DEFAULT_INDENT_WIDTH: usize = 4;
+const MAX_INDENT_WIDTH: usize = 16;
+//settings for a file type are in a section like `[file_types.python]`
+const FILE_TYPES_SECTION: &str = "file_types.";
+
+//settings read from a small subset of TOML: `key = value` pairs, `[section]` headers and `#` comments
+pub struct Config {
+    pub readonly: bool,
+    pub clipboard_osc52: bool,
+    pub clipboard_helper: String,
+    pub theme: String,
+    pub line_numbers: LineNumbers,
+    pub soft_wrap: bool,
+    //the columns between tab stops
+    pub tab_width: usize,
+    pub indentation: IndentationSettings,
+}
+
+//how the gutter left of the text numbers the lines, if it is shown at all
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineNumbers {
+    #[default]
+    Off,
+    Absolute,
+    //counts the lines away from the caret, while the caret's line shows its own number
+    Relative,
+}
+
+//how Tab indents: with spaces up to the next multiple of the width, or with a tab character
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Indentation {
+    pub expand_tabs: bool,
+    pub width: usize,
+}
+
+impl Default for Indentation {
+    fn default() -> Self {
+        Self {
+            expand_tabs: true,
+            width: DEFAULT_INDENT_WIDTH,
+        }
+    }
+}
+
+//the indentation for files of any type, and the file types which override it
+#[derive(Clone, Default)]
+pub struct IndentationSettings {
+    pub default: Indentation,
+    pub file_types: Vec<(String, Indentation)>,
+}
+
+impl IndentationSettings {
+    pub fn get(&self, file_type: Option<&str>) -> Indentation {
+        file_type
+            .and_then(|file_type| {
+                self.file_types
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(file_type))
+            })
+            .map_or(self.default, |(_, indentation)| *indentation)
+    }
+
+    //returns the file type's indentation to override, starting from the default one
+    fn get_mut(&mut self, file_type: &str) -> &mut Indentation {
+        let index = self
+            .file_types
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(file_type))
+            .unwrap_or_else(|| {
+                self.file_types.push((file_type.to_string(), self.default));
+                self.file_types.len().saturating_sub(1)
+            });
+        &mut self.file_types[index].1
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            readonly: false,
+            clipboard_osc52: true,
+            clipboard_helper: "auto".to_string(),
+            theme: "default".to_string(),
+            line_numbers: LineNumbers::default(),
+            soft_wrap: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+            indentation: IndentationSettings::default(),
+        }
+    }
+}
+
+impl Config {
+    //loads the given file, or the default config file if it exists
+    pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let contents = match path {
+            Some(path) => fs::read_to_string(path)?,
+            None => match Self::directory().map(|dir| dir.join(CONFIG_FILE_NAME)) {
+                Some(path) if path.is_file() => fs::read_to_string(path)?,
+                _ => return Ok(Self::default()),
+            },
+        };
+        Self::parse(&contents).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+
+    //$XDG_CONFIG_HOME/hecto, falling back to ~/.config/hecto
+    pub fn directory() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join(NAME))
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        parse_entries(contents, |key, value| config.apply(key, value))
+            .map_err(|err| format!("config {err}"))?;
+        Ok(config)
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "readonly" => self.readonly = parse_bool(value)?,
+            "clipboard.osc52" => self.clipboard_osc52 = parse_bool(value)?,
+            "clipboard.helper" => self.clipboard_helper = parse_string(value)?,
+            "theme" => self.theme = parse_string(value)?,
+            "soft_wrap" => self.soft_wrap = parse_bool(value)?,
+            "tab_width" => self.tab_width = parse_number(value, 1..=MAX_TAB_WIDTH)?,
+            "expand_tabs" | "indent_width" => {
+                Self::apply_indentation(&mut self.indentation.default, key, value)?;
+            }
+            "line_numbers" => {
+                self.line_numbers = match parse_string(value)?.as_str() {
+                    "off" => LineNumbers::Off,
+                    "absolute" => LineNumbers::Absolute,
+                    "relative" => LineNumbers::Relative,
+                    other => return Err(format!("unknown line numbers {other}")),
+                }
+            }
+            _ => {
+                let Some((file_type, setting)) = key
+                    .strip_prefix(FILE_TYPES_SECTION)
+                    .and_then(|key| key.rsplit_once('.'))
+                else {
+                    return Err(format!("unknown setting {key}"));
+                };
+                Self::apply_indentation(self.indentation.get_mut(file_type), setting, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_indentation(
+        indentation: &mut Indentation,
+        key: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        match key {
+            "expand_tabs" => indentation.expand_tabs = parse_bool(value)?,
+            "indent_width" => indentation.width = parse_number(value, 1..=MAX_INDENT_WIDTH)?,
+            _ => return Err(format!("unknown setting {key}")),
+        }
+        Ok(())
+    }
+}
+
+//passes each `key = value` pair to apply, prefixing keys within a `[section]` with its name
+pub fn parse_entries(
+    contents: &str,
+    mut apply: impl FnMut(&str, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut section = String::new();
+    let mut lines = contents.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let line_number = index.saturating_add(1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {line_number}: expected key = value"));
+        };
+        let key = if section.is_empty() {
+            key.trim().to_string()
+        } else {
+            format!("{section}.{}", key.trim())
+        };
+        let mut value = strip_comment(value.trim()).to_string();
+        //an array continues over the following lines up to its closing bracket
+        if value.starts_with('[') {
+            while !unquoted_chars(&value).any(|(_, c)| c == ']') {
+                let Some((_, line)) = lines.next() else {
+                    break;
+                };
+                value.push(' ');
+                value.push_str(strip_comment(line.trim()));
+            }
+        }
+        apply(&key, &value).map_err(|err| format!("line {line_number}: {err}"))?;
+    }
+    Ok(())
+}
+
+//returns the characters of the value outside of strings, with their byte indices
+fn unquoted_chars(value: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote = None;
+    let mut is_escaped = false;
+    value.char_indices().filter(move |&(_, c)| {
+        let was_unquoted = quote.is_none();
+        match (quote, c) {
+            (Some('"'), '\\') if !is_escaped => {
+                is_escaped = true;
+                return false;
+            }
+            (Some(open), _) if c == open && !is_escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            _ => {}
+        }
+        is_escaped = false;
+        was_unquoted && quote.is_none()
+    })
+}
+
+//cuts a trailing `#` comment, unless the `#` is within a string
+fn strip_comment(value: &str) -> &str {
+    unquoted_chars(value)
+        .find(|&(_, c)| c == '#')
+        .map_or(value, |(index, _)| value[..index].trim_end())
+}
+
+//splits a "basic" string with escapes or a 'literal' one from the start of the value,
+//returning it and the rest of the value
+fn split_string(value: &str) -> Result<(String, &str), String> {
+    let mut chars = value.char_indices();
+    let Some((_, quote @ ('"' | '\''))) = chars.next() else {
+        return Err(format!("expected a quoted string, found {value}"));
+    };
+    let mut result = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            _ if c == quote => return Ok((result, &value[index.saturating_add(1)..])),
+            '\\' if quote == '"' => match chars.next() {
+                Some((_, 'n')) => result.push('\n'),
+                Some((_, 't')) => result.push('\t'),
+                Some((_, escaped @ ('"' | '\\'))) => result.push(escaped),
+                _ => return Err(format!("invalid escape in {value}")),
+            },
+            _ => result.push(c),
+        }
+    }
+    Err(format!("unterminated string {value}"))
+}
+
+pub fn parse_string(value: &str) -> Result<String, String> {
+    match split_string(value)? {
+        (string, "") => Ok(string),
+        _ => Err(format!("expected a single quoted string, found {value}")),
+    }
+}
+
+pub fn parse_string_array(value: &str) -> Result<Vec<String>, String> {
+    let Some(mut rest) = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    else {
+        return Err(format!("expected an array of strings, found {value}"));
+    };
+    let mut result = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(result);
+        }
+        let (string, after) = split_string(rest)?;
+        result.push(string);
+        rest = after.trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after;
+        } else if !rest.is_empty() {
+            return Err(format!("expected a comma in {value}"));
+        }
+    }
+}
+
+pub fn parse_number(value: &str, range: RangeInclusive<usize>) -> Result<usize, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|number| range.contains(number))
+        .ok_or_else(|| {
+            format!(
+                "expected a number from {} to {}, found {value}",
+                range.start(),
+                range.end()
+            )
+        })
+}
+
+pub fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("expected true or false, found {value}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(contents: &str) -> Result<Vec<(String, String)>, String> {
+        let mut result = Vec::new();
+        parse_entries(contents, |key, value| {
+            result.push((key.to_string(), value.to_string()));
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    #[test]
+    fn entries_are_prefixed_with_their_section() {
+        let contents = "readonly = true\n\n[clipboard]\n# a comment\nosc52 = false # why not\n";
+        assert_eq!(
+            entries(contents),
+            Ok(vec![
+                ("readonly".to_string(), "true".to_string()),
+                ("clipboard.osc52".to_string(), "false".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn entry_without_value_is_an_error() {
+        assert_eq!(
+            entries("readonly = true\nreadonly\n"),
+            Err("line 2: expected key = value".to_string())
+        );
+    }
+
+    #[test]
+    fn comments_are_kept_within_strings() {
+        assert_eq!(strip_comment(r#""a # b" # c"#), r#""a # b""#);
+        assert_eq!(strip_comment("'a # b' # c"), "'a # b'");
+        assert_eq!(strip_comment(r#""a \" # b" # c"#), r#""a \" # b""#);
+        assert_eq!(strip_comment("true # c"), "true");
+    }
+
+    #[test]
+    fn strings_are_unescaped() {
+        assert_eq!(
+            parse_string(r#""a\t\"b\"\\""#),
+            Ok("a\t\"b\"\\".to_string())
+        );
+        assert_eq!(parse_string(r"'a\t'"), Ok(r"a\t".to_string()));
+        assert!(parse_string(r#""a" "b""#).is_err());
+        assert!(parse_string(r#""a"#).is_err());
+    }
+
+    #[test]
+    fn string_arrays_are_parsed() {
+        assert_eq!(
+            parse_string_array(r#"["a", 'b',"c]",]"#),
+            Ok(vec!["a".to_string(), "b".to_string(), "c]".to_string()])
+        );
+        assert_eq!(parse_string_array("[]"), Ok(Vec::new()));
+        assert!(parse_string_array(r#"["a" "b"]"#).is_err());
+        assert!(parse_string_array(r#""a""#).is_err());
+    }
+
+    #[test]
+    fn arrays_continue_up_to_their_closing_bracket() {
+        let contents = "keywords = [\n    \"when\", # first\n    # between\n    \"]\",\n    'then',\n]\nname = \"rules\"\n";
+        let entries = entries(contents).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            parse_string_array(&entries[0].1),
+            Ok(vec![
+                "when".to_string(),
+                "]".to_string(),
+                "then".to_string()
+            ])
+        );
+        assert_eq!(entries[1], ("name".to_string(), r#""rules""#.to_string()));
+    }
+
+    #[test]
+    fn unclosed_array_is_an_error() {
+        let result = parse_entries("keywords = [\n    \"when\",\n", |_, value| {
+            parse_string_array(value).map(|_| ())
+        });
+        assert!(result.is_err_and(|err| err.starts_with("line 1: ")));
+    }
+
+    #[test]
+    fn numbers_are_checked_against_their_range() {
+        assert_eq!(parse_number("8", 1..=16), Ok(8));
+        assert!(parse_number("0", 1..=16).is_err());
+        assert!(parse_number("-1", 1..=16).is_err());
+        assert!(parse_number("four", 1..=16).is_err());
+    }
+
+    #[test]
+    fn indentation_is_set_per_file_type() {
+        let config = Config::parse(
+            "indent_width = 2\n[file_types.python]\nindent_width = 4\n[file_types.go]\nexpand_tabs = false\n",
+        )
+        .unwrap();
+        let indentation = &config.indentation;
+        assert_eq!(indentation.get(None).width, 2);
+        assert_eq!(indentation.get(Some("Python")).width, 4);
+        assert!(!indentation.get(Some("Go")).expand_tabs);
+        assert_eq!(indentation.get(Some("Go")).width, 2);
+        assert!(indentation.get(Some("Rust")).expand_tabs);
+    }
+
+    #[test]
+    fn unknown_setting_is_an_error() {
+        assert!(Config::parse("[file_types.python]\ntab_size = 4\n").is_err());
+        assert!(Config::parse("colour = true\n").is_err());
+    }
+}
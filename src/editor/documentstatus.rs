@@ -1,24 +1,52 @@
 #[derive(Default, PartialEq, Eq, Debug)]
 pub struct DocumentStatus {
+    //the lines counted for the file's content, which leave out comments in git messages
     pub num_lines: usize,
+    //all lines, which the caret's line is counted among
+    pub total_lines: usize,
     pub current_caret_line: usize,
     pub file_name: String,
     pub is_modified: bool,
+    pub is_readonly: bool,
+    pub current_line_width: usize,
+    pub column_limit: Option<usize>,
+    pub file_type: Option<String>,
 }
 
 impl DocumentStatus {
     pub fn num_lines_to_string(&self) -> String {
-        let result = format!("{}", self.num_lines,);
-        return result;
+        format!("{}", self.num_lines)
     }
 
     pub fn is_modified_to_string(&self) -> String {
-        let result = format!("{}", if self.is_modified { "(modified)" } else { "" },);
-        return result;
+        let result = if self.is_modified { "(modified)" } else { "" };
+        result.to_string()
+    }
+
+    pub fn is_readonly_to_string(&self) -> String {
+        let result = if self.is_readonly { "(read-only)" } else { "" };
+        result.to_string()
+    }
+
+    pub fn column_limit_to_string(&self) -> String {
+        match self.column_limit {
+            Some(limit) if self.current_line_width > limit => {
+                format!("line exceeds {limit} columns | ")
+            }
+            Some(limit) => format!("{}/{limit} | ", self.current_line_width),
+            None => String::new(),
+        }
+    }
+
+    pub fn file_type_to_string(&self) -> String {
+        format!("{} | ", self.file_type.as_deref().unwrap_or("Text"))
     }
 
     pub fn caret_position_to_string(&self) -> String {
-        let result = format!("{}/{}", self.current_caret_line, self.num_lines);
-        return result;
+        format!(
+            "{}/{}",
+            self.current_caret_line.saturating_add(1),
+            self.total_lines
+        )
     }
 }
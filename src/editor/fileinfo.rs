@@ -1,22 +1,179 @@
+use super::syntax::Syntax;
 use std::{
     fmt::{self, Display},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    rc::Rc,
 };
+
+const GIT_MESSAGE_FILE_NAMES: [&str; 5] = [
+    "COMMIT_EDITMSG",
+    "MERGE_MSG",
+    "TAG_EDITMSG",
+    "SQUASH_MSG",
+    "EDIT_DESCRIPTION",
+];
+
+//modelines are looked for within this many lines of the start and end of a file
+pub const MODELINE_SEARCH_LINES: usize = 5;
+
+//file types, with their extensions and the interpreters named by a shebang line
+const FILE_TYPES: [(&str, &[&str], &[&str]); 17] = [
+    ("Rust", &["rs"], &[]),
+    ("C", &["c", "h"], &[]),
+    ("C++", &["cpp", "cc", "cxx", "hpp", "hh"], &[]),
+    ("Go", &["go"], &[]),
+    ("Python", &["py", "pyw"], &["python"]),
+    (
+        "Shell",
+        &["sh", "bash", "zsh"],
+        &["sh", "bash", "zsh", "dash", "ksh"],
+    ),
+    ("JavaScript", &["js", "mjs", "cjs"], &["node"]),
+    ("TypeScript", &["ts"], &[]),
+    ("Ruby", &["rb"], &["ruby"]),
+    ("Perl", &["pl", "pm"], &["perl"]),
+    ("Lua", &["lua"], &["lua"]),
+    ("TOML", &["toml"], &[]),
+    ("JSON", &["json"], &[]),
+    ("YAML", &["yaml", "yml"], &[]),
+    ("Markdown", &["md", "markdown"], &[]),
+    ("HTML", &["html", "htm"], &[]),
+    ("CSS", &["css"], &[]),
+];
+
 #[derive(Default, Debug, Clone)]
 pub struct FileInfo {
     pub path: Option<PathBuf>,
+    pub is_directory: bool,
+    pub file_type: Option<String>,
 }
 
 impl FileInfo {
     pub fn from(file_name: &str) -> Self {
         Self {
             path: Some(PathBuf::from(file_name)),
+            is_directory: false,
+            file_type: None,
+        }
+    }
+
+    //detects the file type from a modeline, then a shebang on the first of the given lines,
+    //then the extension; the lines are those at the start and end of the file
+    pub fn detect_file_type(&mut self, lines: &[String], syntaxes: &[Rc<Syntax>]) {
+        let find_file_type = |name: &str| Self::find_file_type(name, syntaxes);
+        let from_extension = || {
+            let extension = self.path.as_ref()?.extension()?.to_str()?;
+            find_file_type(&extension.to_lowercase())
+        };
+        self.file_type = lines
+            .iter()
+            .find_map(|line| Self::parse_modeline(line))
+            .and_then(|name| find_file_type(&name))
+            .or_else(|| {
+                lines
+                    .first()
+                    .and_then(|line| Self::parse_shebang(line))
+                    .and_then(|name| find_file_type(&name))
+            })
+            .or_else(from_extension);
+    }
+
+    //matches a file type by its name, one of its extensions or interpreters, preferring those
+    //of loaded syntax definitions
+    fn find_file_type(name: &str, syntaxes: &[Rc<Syntax>]) -> Option<String> {
+        let matches = |file_type: &str, extensions: &[&str], interpreters: &[&str]| {
+            file_type.eq_ignore_ascii_case(name)
+                || extensions.contains(&name)
+                || interpreters.contains(&name)
+        };
+        syntaxes
+            .iter()
+            .find(|syntax| {
+                let extensions: Vec<&str> = syntax.extensions.iter().map(String::as_str).collect();
+                let interpreters: Vec<&str> =
+                    syntax.interpreters.iter().map(String::as_str).collect();
+                matches(&syntax.file_type, &extensions, &interpreters)
+            })
+            .map(|syntax| syntax.file_type.clone())
+            .or_else(|| {
+                FILE_TYPES
+                    .iter()
+                    .find(|(file_type, extensions, interpreters)| {
+                        matches(file_type, extensions, interpreters)
+                    })
+                    .map(|(file_type, _, _)| (*file_type).to_string())
+            })
+    }
+
+    //returns the interpreter of "#!/bin/sh" or "#!/usr/bin/env python3", without its version
+    fn parse_shebang(line: &str) -> Option<String> {
+        let mut words = line.strip_prefix("#!")?.split_whitespace();
+        let mut program = words.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = words.find(|word| !word.starts_with('-'))?;
+        }
+        Some(
+            program
+                .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+                .to_string(),
+        )
+    }
+
+    //returns the file type set by a vim modeline such as "vim: set ft=rust:", or by an emacs
+    //one such as "-*- mode: rust -*-"
+    fn parse_modeline(line: &str) -> Option<String> {
+        if let Some((_, rest)) = line.split_once("-*-") {
+            let (variables, _) = rest.split_once("-*-")?;
+            if !variables.contains(':') {
+                return Some(variables.trim().to_lowercase());
+            }
+            return variables.split(';').find_map(|variable| {
+                let (name, value) = variable.split_once(':')?;
+                (name.trim() == "mode").then(|| value.trim().to_lowercase())
+            });
+        }
+        let options = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+            let index = line.find(marker)?;
+            let is_word_start = line[..index]
+                .chars()
+                .next_back()
+                .is_none_or(char::is_whitespace);
+            is_word_start.then(|| &line[index.saturating_add(marker.len())..])
+        })?;
+        options.split([' ', ':']).find_map(|option| {
+            let (name, value) = option.split_once('=')?;
+            ["ft", "filetype", "syntax", "syn"]
+                .contains(&name)
+                .then(|| value.to_lowercase())
+        })
+    }
+
+    //whether git opened this file for editing a commit, merge or tag message
+    pub fn is_git_message(&self) -> bool {
+        self.path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| GIT_MESSAGE_FILE_NAMES.contains(&name))
+    }
+
+    pub fn from_directory(path: &Path) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            is_directory: true,
+            file_type: None,
         }
     }
 }
 
 impl Display for FileInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_directory {
+            if let Some(path) = &self.path {
+                let separator = if path.parent().is_some() { "/" } else { "" };
+                return write!(f, "{}{separator}", path.display());
+            }
+        }
         let name = self
             .path
             .as_ref()
@@ -1,4 +1,5 @@
 use super::terminal::{Size, Terminal};
+use super::theme::Style;
 use std::time::Duration;
 use std::time::Instant;
 const DEFAULT_DURATION: Duration = Duration::new(5, 0);
@@ -8,6 +9,7 @@ pub struct MessageBar {
     needs_redraw: bool,
     is_cleared: bool,
     message: Message,
+    style: Style,
 }
 
 struct Message {
@@ -36,9 +38,15 @@ impl MessageBar {
             needs_redraw: true,
             is_cleared: false,
             message: Message::default(),
+            style: Style::default(),
         }
     }
 
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+        self.needs_redraw = true;
+    }
+
     pub fn update_msg(&mut self, msg: &str) {
         self.message = Message {
             text: msg.to_string(),
@@ -52,6 +60,10 @@ impl MessageBar {
         self.needs_redraw = true;
     }
 
+    pub fn set_needs_redraw(&mut self) {
+        self.needs_redraw = true;
+    }
+
     pub fn render(&mut self) {
         self.needs_redraw = self.needs_redraw || (!self.is_cleared && self.message.is_expired());
         if !self.needs_redraw {
@@ -66,7 +78,7 @@ impl MessageBar {
         } else {
             &self.message.text
         };
-        let result = Terminal::print_row(size.height.saturating_sub(1), msg);
+        let result = Terminal::print_styled_row(size.height.saturating_sub(1), msg, &self.style);
         debug_assert!(result.is_ok(), "Failed to render message bar");
         self.needs_redraw = false;
     }
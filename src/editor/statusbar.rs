@@ -1,5 +1,6 @@
 use super::documentstatus::DocumentStatus;
 use super::terminal::{Size, Terminal};
+use super::theme::Style;
 pub struct StatusBar {
     pub current_status: DocumentStatus,
     needs_redraw: bool,
@@ -7,6 +8,7 @@ pub struct StatusBar {
     position_y: usize,
     margin_bottom: usize,
     is_visible: bool,
+    style: Style,
 }
 
 impl StatusBar {
@@ -19,6 +21,7 @@ impl StatusBar {
             position_y: 0,
             margin_bottom,
             is_visible: false,
+            style: Style::default(),
         };
         status_bar.resize(size);
         status_bar
@@ -41,6 +44,11 @@ impl StatusBar {
         self.is_visible = is_visible;
     }
 
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+        self.needs_redraw = true;
+    }
+
     pub fn render(&mut self) {
         if !self.needs_redraw || !self.is_visible {
             return;
@@ -48,11 +56,17 @@ impl StatusBar {
         if let Ok(size) = Terminal::get_size() {
             let num_lines = self.current_status.num_lines_to_string();
             let modified_status = self.current_status.is_modified_to_string();
+            let readonly_status = self.current_status.is_readonly_to_string();
             let beginning = format!(
-                "{} - {num_lines} {modified_status}",
+                "{} - {num_lines} {readonly_status}{modified_status}",
                 self.current_status.file_name
             );
-            let position = self.current_status.caret_position_to_string();
+            let position = format!(
+                "{}{}{}",
+                self.current_status.column_limit_to_string(),
+                self.current_status.file_type_to_string(),
+                self.current_status.caret_position_to_string()
+            );
             let remainder_len = size.width.saturating_sub(beginning.len());
             let status = format!("{beginning}{position:>remainder_len$}");
             let to_print = if status.len() <= size.width {
@@ -60,7 +74,13 @@ impl StatusBar {
             } else {
                 String::new()
             };
-            let result = Terminal::invert_print(&to_print, self.position_y);
+            //padded, as reverse video does not apply to the cleared rest of the row
+            let width = size.width;
+            let result = Terminal::print_styled_row(
+                self.position_y,
+                &format!("{to_print:width$.width$}"),
+                &self.style,
+            );
             debug_assert!(result.is_ok(), "Failed to render status bar");
             self.needs_redraw = false;
         }
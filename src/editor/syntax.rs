@@ -0,0 +1,184 @@
+use super::config::{parse_bool, parse_entries, parse_string, parse_string_array, Config};
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::rc::Rc;
+
+//syntax definitions are read from the *.toml files in this directory of the config directory
+const SYNTAX_DIRECTORY_NAME: &str = "syntax";
+
+const RUST_KEYWORDS: [&str; 39] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "yield",
+];
+
+const RUST_TYPES: [&str; 17] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64", "bool", "char", "str",
+];
+
+//the rules for highlighting one language; a definition file sets them with the same keys,
+//e.g. `name = "Rules"`, `extensions = ["rules"]`, `keywords = ["when", "then"]`,
+//`line_comment = "#"`, `block_comment = ["/*", "*/"]` and `strings = ['"', "'"]`
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Syntax {
+    pub file_type: String,
+    pub extensions: Vec<String>,
+    pub interpreters: Vec<String>,
+    pub keywords: Vec<String>,
+    pub types: Vec<String>,
+    //whether any word starting with an uppercase letter names a type
+    pub capitalized_types: bool,
+    pub line_comment: Option<String>,
+    pub block_comment: Option<(String, String)>,
+    pub nested_comments: bool,
+    pub string_delimiters: Vec<char>,
+    pub multiline_strings: bool,
+    //Rust's r"..." and r#"..."# strings, which have no escapes
+    pub raw_strings: bool,
+    //'c' is a character while 'a without a closing quote is a lifetime or label
+    pub char_literals: bool,
+}
+
+impl Syntax {
+    pub fn rust() -> Self {
+        Self {
+            file_type: "Rust".to_string(),
+            keywords: RUST_KEYWORDS.map(str::to_string).to_vec(),
+            types: RUST_TYPES.map(str::to_string).to_vec(),
+            capitalized_types: true,
+            line_comment: Some("//".to_string()),
+            block_comment: Some(("/*".to_string(), "*/".to_string())),
+            nested_comments: true,
+            string_delimiters: vec!['"'],
+            multiline_strings: true,
+            raw_strings: true,
+            char_literals: true,
+            ..Self::default()
+        }
+    }
+
+    //returns the definitions from the config directory, followed by the built-in ones they may
+    //replace
+    pub fn load_all() -> Result<Vec<Rc<Self>>, Error> {
+        let mut syntaxes = match Config::directory() {
+            Some(directory) => Self::load_directory(&directory.join(SYNTAX_DIRECTORY_NAME))?,
+            None => Vec::new(),
+        };
+        syntaxes.push(Self::rust());
+        Ok(syntaxes.into_iter().map(Rc::new).collect())
+    }
+
+    fn load_directory(directory: &Path) -> Result<Vec<Self>, Error> {
+        if !directory.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut paths: Vec<_> = fs::read_dir(directory)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        paths.retain(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        });
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                Self::parse(&fs::read_to_string(path)?).map_err(|err| {
+                    Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display()))
+                })
+            })
+            .collect()
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut syntax = Self::default();
+        parse_entries(contents, |key, value| syntax.apply(key, value))?;
+        if syntax.file_type.is_empty() {
+            return Err("missing name".to_string());
+        }
+        Ok(syntax)
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "name" => self.file_type = parse_string(value)?,
+            "extensions" => self.extensions = parse_string_array(value)?,
+            "interpreters" => self.interpreters = parse_string_array(value)?,
+            "keywords" => self.keywords = parse_string_array(value)?,
+            "types" => self.types = parse_string_array(value)?,
+            "capitalized_types" => self.capitalized_types = parse_bool(value)?,
+            //an empty marker would match everywhere without moving past anything
+            "line_comment" => match parse_string(value)? {
+                marker if marker.is_empty() => {
+                    return Err("expected a non-empty comment marker".to_string())
+                }
+                marker => self.line_comment = Some(marker),
+            },
+            "block_comment" => match <[String; 2]>::try_from(parse_string_array(value)?) {
+                Ok([open, close]) if open.is_empty() || close.is_empty() => {
+                    return Err("expected a non-empty comment marker".to_string());
+                }
+                Ok([open, close]) => self.block_comment = Some((open, close)),
+                Err(_) => return Err("expected the opening and closing strings".to_string()),
+            },
+            "nested_comments" => self.nested_comments = parse_bool(value)?,
+            "strings" => {
+                self.string_delimiters = parse_string_array(value)?
+                    .iter()
+                    .map(|delimiter| {
+                        let mut chars = delimiter.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) => Ok(c),
+                            _ => Err(format!("expected a single character, found {delimiter}")),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "multiline_strings" => self.multiline_strings = parse_bool(value)?,
+            "raw_strings" => self.raw_strings = parse_bool(value)?,
+            "char_literals" => self.char_literals = parse_bool(value)?,
+            _ => return Err(format!("unknown setting {key}")),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definition_is_parsed() {
+        let syntax = Syntax::parse(
+            "name = \"Rules\"\nkeywords = [\n    \"when\",\n    \"then\",\n]\nline_comment = \"#\"\nblock_comment = [\"/*\", \"*/\"]\n",
+        )
+        .unwrap();
+        assert_eq!(syntax.file_type, "Rules");
+        assert_eq!(
+            syntax.keywords,
+            vec!["when".to_string(), "then".to_string()]
+        );
+        assert_eq!(syntax.line_comment.as_deref(), Some("#"));
+        assert_eq!(
+            syntax.block_comment,
+            Some(("/*".to_string(), "*/".to_string()))
+        );
+    }
+
+    #[test]
+    fn definition_without_name_is_an_error() {
+        assert!(Syntax::parse("keywords = [\"when\"]\n").is_err());
+    }
+
+    #[test]
+    fn empty_comment_markers_are_an_error() {
+        assert!(Syntax::parse("name = \"Rules\"\nline_comment = \"\"\n").is_err());
+        assert!(Syntax::parse("name = \"Rules\"\nblock_comment = [\"\", \"\"]\n").is_err());
+        assert!(Syntax::parse("name = \"Rules\"\nblock_comment = [\"/*\", \"\"]\n").is_err());
+        assert!(Syntax::parse("name = \"Rules\"\nblock_comment = [\"\", \"*/\"]\n").is_err());
+    }
+}
@@ -1,11 +1,55 @@
+use super::theme::Style;
 use crossterm::cursor::{Hide, MoveTo, Show};
-use crossterm::style::{Attribute, Print};
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::{Attribute, Color, Colored, Print};
 use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, size, Clear, ClearType, DisableLineWrap, EnableLineWrap,
-    EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    disable_raw_mode, enable_raw_mode, size, supports_keyboard_enhancement, Clear, ClearType,
+    DisableLineWrap, EnableLineWrap, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
 };
 use crossterm::{queue, Command};
+use std::env;
 use std::io::{stdout, Error, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//whether initialize pushed keyboard enhancement flags, which terminate has to pop again
+static IS_KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+//the 16 colors of the xterm palette, by their ANSI value
+const ANSI_COLORS: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::DarkRed, [205, 0, 0]),
+    (Color::DarkGreen, [0, 205, 0]),
+    (Color::DarkYellow, [205, 205, 0]),
+    (Color::DarkBlue, [0, 0, 238]),
+    (Color::DarkMagenta, [205, 0, 205]),
+    (Color::DarkCyan, [0, 205, 205]),
+    (Color::Grey, [229, 229, 229]),
+    (Color::DarkGrey, [127, 127, 127]),
+    (Color::Red, [255, 0, 0]),
+    (Color::Green, [0, 255, 0]),
+    (Color::Yellow, [255, 255, 0]),
+    (Color::Blue, [92, 92, 255]),
+    (Color::Magenta, [255, 0, 255]),
+    (Color::Cyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+//the levels of each component in the 6x6x6 color cube of 256-color terminals
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
 
 pub struct Terminal {}
 
@@ -42,6 +86,11 @@ impl Terminal {
     }
 
     pub fn terminate() -> Result<(), Error> {
+        if IS_KEYBOARD_ENHANCED.swap(false, Ordering::Relaxed) {
+            Self::queue_command(PopKeyboardEnhancementFlags)?;
+        }
+        Self::queue_command(DisableBracketedPaste)?;
+        Self::queue_command(DisableMouseCapture)?;
         Self::leave_alternate_screen()?;
         Self::enable_line_wrap()?;
         Self::show_caret()?;
@@ -54,6 +103,17 @@ impl Terminal {
         enable_raw_mode()?;
         Self::enter_alternate_screen()?;
         Self::disable_line_wrap()?;
+        //pastes arrive as one event instead of a key event per character
+        Self::queue_command(EnableBracketedPaste)?;
+        Self::queue_command(EnableMouseCapture)?;
+        //terminals with the kitty keyboard protocol then report modified keys such as
+        //Ctrl+Backspace, which others send as the same byte as a plain key
+        if supports_keyboard_enhancement().unwrap_or(false) {
+            Self::queue_command(PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
+            ))?;
+            IS_KEYBOARD_ENHANCED.store(true, Ordering::Relaxed);
+        }
         Self::clear_screen()?;
         Self::buffer_flush()?;
         Ok(())
@@ -64,20 +124,103 @@ impl Terminal {
         Ok(())
     }
 
-    pub fn invert_print(line_text: &str, row: usize) -> Result<(), Error> {
-        let width = Self::get_size()?.width;
-        Self::print_row(
-            row,
-            &format!(
-                "{}{:width$.width$}{}",
-                Attribute::Reverse,
-                line_text,
-                Attribute::Reset
-            ),
-        )?;
+    //prints a row in the given style, which also fills the rest of the row's background
+    pub fn print_styled_row(row: usize, line_text: &str, style: &Style) -> Result<(), Error> {
+        Self::move_caret_to(Position { row, col: 0 })?;
+        Self::print(&Self::style_sequence(style))?;
+        Self::clear_line()?;
+        Self::print(line_text)?;
+        Self::print(&Attribute::Reset.to_string())?;
         Ok(())
     }
 
+    //returns the escape sequence switching from any style to the given one
+    pub fn style_sequence(style: &Style) -> String {
+        let mut parameters = vec![Attribute::Reset.sgr()];
+        parameters.extend(
+            [
+                Attribute::Bold,
+                Attribute::Italic,
+                Attribute::Underlined,
+                Attribute::Reverse,
+            ]
+            .into_iter()
+            .filter(|&attribute| style.attributes.has(attribute))
+            .map(Attribute::sgr),
+        );
+        parameters.extend(
+            style
+                .foreground
+                .map(|color| Colored::ForegroundColor(color).to_string()),
+        );
+        parameters.extend(
+            style
+                .background
+                .map(|color| Colored::BackgroundColor(color).to_string()),
+        );
+        format!("\x1b[{}m", parameters.join(";"))
+    }
+
+    //NO_COLOR turns colors off, COLORTERM announces truecolor and TERM the number of colors
+    pub fn color_support() -> ColorSupport {
+        let is_set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+        let term = env::var("TERM").unwrap_or_default();
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if is_set("NO_COLOR") || term == "dumb" {
+            ColorSupport::None
+        } else if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    //returns the closest color the terminal can show, if it shows any
+    pub fn fit_color(color: Color, color_support: ColorSupport) -> Option<Color> {
+        let rgb = match color {
+            Color::Rgb { r, g, b } => [r, g, b],
+            Color::AnsiValue(value) => Self::ansi_to_rgb(value),
+            _ => [0; 3],
+        };
+        match (color_support, color) {
+            (ColorSupport::None, _) => None,
+            //the palette's first 16 colors vary between terminals, so only the rest are matched
+            (ColorSupport::Ansi256, Color::Rgb { .. }) => (16..=u8::MAX)
+                .min_by_key(|&value| Self::color_distance(Self::ansi_to_rgb(value), rgb))
+                .map(Color::AnsiValue),
+            (ColorSupport::Ansi16, Color::Rgb { .. } | Color::AnsiValue(_)) => ANSI_COLORS
+                .iter()
+                .min_by_key(|(_, palette_rgb)| Self::color_distance(*palette_rgb, rgb))
+                .map(|(ansi_color, _)| *ansi_color),
+            _ => Some(color),
+        }
+    }
+
+    #[allow(clippy::integer_division)]
+    fn ansi_to_rgb(value: u8) -> [u8; 3] {
+        let index = usize::from(value);
+        if let Some((_, rgb)) = ANSI_COLORS.get(index) {
+            *rgb
+        } else if let Some(cube_index) = index.checked_sub(16).filter(|&index| index < 216) {
+            [cube_index / 36, cube_index / 6 % 6, cube_index % 6].map(|level| CUBE_LEVELS[level])
+        } else {
+            let grey = value
+                .saturating_sub(232)
+                .saturating_mul(10)
+                .saturating_add(8);
+            [grey; 3]
+        }
+    }
+
+    fn color_distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+        a.iter()
+            .zip(b)
+            .map(|(&a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum()
+    }
+
     pub fn get_size() -> Result<Size, Error> {
         let (width_u16, height_u16) = size()?;
         #[allow(clippy::as_conversions)]
@@ -124,18 +267,43 @@ impl Terminal {
         Ok(())
     }
 
-    pub fn print_row(row: usize, line_text: &str) -> Result<(), Error> {
-        Self::move_caret_to(Position { row, col: 0 })?;
-        Self::clear_line()?;
-        Self::print(line_text)?;
+    pub fn set_title(title: &str) -> Result<(), Error> {
+        Self::queue_command(SetTitle(title))?;
         Ok(())
     }
 
-    pub fn set_title(title: &str) -> Result<(), Error> {
-        Self::queue_command(SetTitle(title))?;
+    //asks the terminal to place the text on the system clipboard with an OSC 52 sequence,
+    //which also works over SSH
+    pub fn set_clipboard(text: &str) -> Result<(), Error> {
+        Self::print(&format!(
+            "\x1b]52;c;{}\x07",
+            Self::base64_encode(text.as_bytes())
+        ))?;
         Ok(())
     }
 
+    fn base64_encode(bytes: &[u8]) -> String {
+        let mut result = String::new();
+        for chunk in bytes.chunks(3) {
+            let mut group = [0; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let sextets = [
+                group[0] >> 2,
+                (group[0] & 0x03) << 4 | group[1] >> 4,
+                (group[1] & 0x0f) << 2 | group[2] >> 6,
+                group[2] & 0x3f,
+            ];
+            for (index, sextet) in sextets.into_iter().enumerate() {
+                if index > chunk.len() {
+                    result.push('=');
+                } else {
+                    result.push(char::from(BASE64_ALPHABET[usize::from(sextet)]));
+                }
+            }
+        }
+        result
+    }
+
     pub fn enable_line_wrap() -> Result<(), Error> {
         Self::queue_command(EnableLineWrap)?;
         Ok(())
@@ -0,0 +1,249 @@
+use super::config::{parse_bool, parse_entries, parse_string, Config};
+use super::terminal::{ColorSupport, Terminal};
+use crossterm::style::{Attribute, Attributes, Color};
+use std::fs;
+use std::io::{Error, ErrorKind};
+
+//themes are read from NAME.toml in this directory of the config directory, before the built-in ones
+const THEME_DIRECTORY_NAME: &str = "themes";
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub attributes: Attributes,
+}
+
+impl Style {
+    fn fg(foreground: Color) -> Self {
+        Self {
+            foreground: Some(foreground),
+            ..Self::default()
+        }
+    }
+
+    fn on(self, background: Color) -> Self {
+        Self {
+            background: Some(background),
+            ..self
+        }
+    }
+
+    fn with(self, attribute: Attribute) -> Self {
+        Self {
+            attributes: self.attributes | attribute,
+            ..self
+        }
+    }
+
+    //draws this style over another one, keeping the other's colors where this has none
+    pub fn over(self, other: Self) -> Self {
+        Self {
+            foreground: self.foreground.or(other.foreground),
+            background: self.background.or(other.background),
+            attributes: self.attributes | other.attributes,
+        }
+    }
+
+    fn fit(self, color_support: ColorSupport) -> Self {
+        let fit = |color| Terminal::fit_color(color, color_support);
+        Self {
+            foreground: self.foreground.and_then(fit),
+            background: self.background.and_then(fit),
+            ..self
+        }
+    }
+}
+
+//the styles of everything hecto draws, by the role it plays
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub text: Style,
+    pub status_bar: Style,
+    pub message_bar: Style,
+    pub selection: Style,
+    pub line_number: Style,
+    pub current_line_number: Style,
+    pub keyword: Style,
+    pub type_name: Style,
+    pub string: Style,
+    pub comment: Style,
+    pub number: Style,
+}
+
+//the terminal's own colors, which suit both dark and light terminals
+impl Default for Theme {
+    fn default() -> Self {
+        let reverse = Style::default().with(Attribute::Reverse);
+        Self {
+            text: Style::default(),
+            status_bar: reverse,
+            message_bar: Style::default(),
+            selection: reverse,
+            line_number: Style::fg(Color::DarkGrey),
+            current_line_number: Style::default().with(Attribute::Bold),
+            keyword: Style::fg(Color::Yellow),
+            type_name: Style::fg(Color::Cyan),
+            string: Style::fg(Color::Green),
+            comment: Style::fg(Color::DarkGrey),
+            number: Style::fg(Color::Magenta),
+        }
+    }
+}
+
+impl Theme {
+    fn dark() -> Self {
+        Self {
+            text: Style::fg(hex(0x00ab_b2bf)).on(hex(0x0028_2c34)),
+            status_bar: Style::fg(hex(0x00d7_dae0)).on(hex(0x003e_4452)),
+            message_bar: Style::fg(hex(0x00ab_b2bf)).on(hex(0x0021_252b)),
+            selection: Style::default().on(hex(0x0044_4b5a)),
+            line_number: Style::fg(hex(0x004b_5263)),
+            current_line_number: Style::fg(hex(0x00ab_b2bf)),
+            keyword: Style::fg(hex(0x00c6_78dd)),
+            type_name: Style::fg(hex(0x00e5_c07b)),
+            string: Style::fg(hex(0x0098_c379)),
+            comment: Style::fg(hex(0x005c_6370)).with(Attribute::Italic),
+            number: Style::fg(hex(0x00d1_9a66)),
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            text: Style::fg(hex(0x0038_3a42)).on(hex(0x00fa_fafa)),
+            status_bar: Style::fg(hex(0x0038_3a42)).on(hex(0x00d4_d4d4)),
+            message_bar: Style::fg(hex(0x0038_3a42)).on(hex(0x00f0_f0f0)),
+            selection: Style::default().on(hex(0x00bf_ceff)),
+            line_number: Style::fg(hex(0x009d_9d9f)),
+            current_line_number: Style::fg(hex(0x0038_3a42)),
+            keyword: Style::fg(hex(0x00a6_26a4)),
+            type_name: Style::fg(hex(0x00c1_8401)),
+            string: Style::fg(hex(0x0050_a14f)),
+            comment: Style::fg(hex(0x00a0_a1a7)).with(Attribute::Italic),
+            number: Style::fg(hex(0x0098_6801)),
+        }
+    }
+
+    fn fit(self, color_support: ColorSupport) -> Self {
+        Self {
+            text: self.text.fit(color_support),
+            status_bar: self.status_bar.fit(color_support),
+            message_bar: self.message_bar.fit(color_support),
+            selection: self.selection.fit(color_support),
+            line_number: self.line_number.fit(color_support),
+            current_line_number: self.current_line_number.fit(color_support),
+            keyword: self.keyword.fit(color_support),
+            type_name: self.type_name.fit(color_support),
+            string: self.string.fit(color_support),
+            comment: self.comment.fit(color_support),
+            number: self.number.fit(color_support),
+        }
+    }
+
+    fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    //loads the named theme with its colors reduced to those the terminal can show; without
+    //colors, the default theme still marks the selection and status bar in reverse video
+    pub fn load(name: &str) -> Result<Self, Error> {
+        let color_support = Terminal::color_support();
+        if color_support == ColorSupport::None {
+            return Ok(Self::default().fit(color_support));
+        }
+        let path = Config::directory()
+            .map(|dir| dir.join(THEME_DIRECTORY_NAME).join(format!("{name}.toml")));
+        let theme = match path {
+            Some(path) if path.is_file() => {
+                Self::parse(&fs::read_to_string(&path)?).map_err(|err| {
+                    Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display()))
+                })?
+            }
+            _ => Self::built_in(name)
+                .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("unknown theme {name}")))?,
+        };
+        Ok(theme.fit(color_support))
+    }
+
+    //a theme file may start from a built-in theme with `base = "dark"`, then set the styles of
+    //roles in sections such as `[keyword]` with `foreground`, `background`, `bold`, `italic`,
+    //`underline` and `reverse`
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut theme = Self::default();
+        parse_entries(contents, |key, value| theme.apply(key, value))?;
+        Ok(theme)
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if key == "base" {
+            let name = parse_string(value)?;
+            *self = Self::built_in(&name).ok_or_else(|| format!("unknown theme {name}"))?;
+            return Ok(());
+        }
+        let Some((role, property)) = key.split_once('.') else {
+            return Err(format!("unknown setting {key}"));
+        };
+        let style = match role {
+            "text" => &mut self.text,
+            "status_bar" => &mut self.status_bar,
+            "message_bar" => &mut self.message_bar,
+            "selection" => &mut self.selection,
+            "line_number" => &mut self.line_number,
+            "current_line_number" => &mut self.current_line_number,
+            "keyword" => &mut self.keyword,
+            "type" => &mut self.type_name,
+            "string" => &mut self.string,
+            "comment" => &mut self.comment,
+            "number" => &mut self.number,
+            _ => return Err(format!("unknown role {role}")),
+        };
+        let attribute = match property {
+            "foreground" => {
+                style.foreground = Some(parse_color(value)?);
+                return Ok(());
+            }
+            "background" => {
+                style.background = Some(parse_color(value)?);
+                return Ok(());
+            }
+            "bold" => Attribute::Bold,
+            "italic" => Attribute::Italic,
+            "underline" => Attribute::Underlined,
+            "reverse" => Attribute::Reverse,
+            _ => return Err(format!("unknown setting {key}")),
+        };
+        if parse_bool(value)? {
+            style.attributes.set(attribute);
+        } else {
+            style.attributes.unset(attribute);
+        }
+        Ok(())
+    }
+}
+
+const fn hex(rgb: u32) -> Color {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    Color::Rgb { r, g, b }
+}
+
+//parses "#rrggbb", an ANSI color number or a name such as "dark_cyan"
+fn parse_color(value: &str) -> Result<Color, String> {
+    let name = parse_string(value)?;
+    if let Some(digits) = name.strip_prefix('#') {
+        if digits.len() == 6 {
+            if let Ok(rgb) = u32::from_str_radix(digits, 16) {
+                return Ok(hex(rgb));
+            }
+        }
+    } else if let Ok(ansi_value) = name.parse::<u8>() {
+        return Ok(Color::AnsiValue(ansi_value));
+    } else if let Ok(color) = Color::try_from(name.as_str()) {
+        return Ok(color);
+    }
+    Err(format!("unknown color {name}"))
+}
@@ -1,13 +1,30 @@
 mod buffer;
+mod highlighter;
 mod line;
-use super::command::{Direction, EditCommand};
+use super::command::{Direction, EditCommand, LineCommand, MouseCommand};
+use super::config::{Indentation, IndentationSettings, LineNumbers, DEFAULT_TAB_WIDTH};
 use super::documentstatus::DocumentStatus;
-use super::fileinfo::FileInfo;
+use super::fileinfo::{FileInfo, MODELINE_SEARCH_LINES};
+use super::syntax::Syntax;
 use super::terminal::{Position, Size, Terminal};
+use super::theme::{Style, Theme};
 use super::{NAME, VERSION};
 use buffer::Buffer;
-use std::cmp::min;
-use std::io::Error;
+use line::Line;
+use std::cmp::{min, Ordering};
+use std::io::{Error, ErrorKind};
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const GIT_MESSAGE_COLUMN_LIMIT: usize = 72;
+const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(500);
+const SCROLL_LINES: usize = 3;
+//brackets which open and close a block, whose lines are indented one level deeper
+const BLOCK_BRACKETS: [(char, char); 3] = [('{', '}'), ('(', ')'), ('[', ']')];
+//file types in which a line ending with a colon opens a block
+const COLON_BLOCK_FILE_TYPES: [&str; 2] = ["Python", "YAML"];
 
 pub struct View {
     buffer: Buffer,
@@ -15,15 +32,43 @@ pub struct View {
     size: Size,
     text_location: Location,
     scroll_offset: Position,
+    //while wrapping, the visual row of the top line at which the view starts
+    scroll_wrap_row: usize,
     margin_bottom: usize,
+    is_readonly: bool,
+    selection_anchor: Option<Location>,
+    last_click: Option<(Instant, Location)>,
+    //the rendered column vertical moves try to keep, valid while the caret is where they left it
+    sticky_col: Option<(usize, Location)>,
+    syntaxes: Vec<Rc<Syntax>>,
+    theme: Theme,
+    line_numbers: LineNumbers,
+    //the caret's line when the gutter was last drawn, as moving off it changes the gutter
+    gutter_caret_line_index: usize,
+    soft_wrap: bool,
+    tab_width: usize,
+    indentation_settings: IndentationSettings,
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
 pub struct Location {
     grapheme_index: usize,
     line_index: usize,
 }
 
+//locations are ordered by their position in the document
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.line_index, self.grapheme_index).cmp(&(other.line_index, other.grapheme_index))
+    }
+}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl View {
     pub fn new(margin_bottom: usize) -> Self {
         let size = Terminal::get_size().unwrap_or_default();
@@ -37,22 +82,332 @@ impl View {
             margin_bottom,
             text_location: Location::default(),
             scroll_offset: Position::default(),
+            scroll_wrap_row: 0,
+            is_readonly: false,
+            selection_anchor: None,
+            last_click: None,
+            sticky_col: None,
+            syntaxes: Vec::new(),
+            theme: Theme::default(),
+            line_numbers: LineNumbers::default(),
+            gutter_caret_line_index: 0,
+            soft_wrap: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+            indentation_settings: IndentationSettings::default(),
         }
     }
 
     pub fn load(&mut self, file_name: &str) -> Result<(), Error> {
-        let buffer = Buffer::load(file_name)?;
+        let path = Path::new(file_name);
+        let buffer = if path.is_dir() {
+            Buffer::load_directory(path)?
+        } else {
+            Buffer::load(file_name, &self.syntaxes)?
+        };
         self.buffer = buffer;
+        self.detect_indentation();
+        self.selection_anchor = None;
+        self.text_location = Location::default();
+        self.scroll_offset = Position::default();
+        self.scroll_wrap_row = 0;
         self.needs_redraw = true;
         Ok(())
     }
 
+    pub fn load_text(&mut self, text: &str) {
+        self.buffer = Buffer::from_text(text, &self.syntaxes);
+        self.detect_indentation();
+        self.selection_anchor = None;
+        self.text_location = Location::default();
+        self.scroll_offset = Position::default();
+        self.scroll_wrap_row = 0;
+        self.needs_redraw = true;
+    }
+
+    pub fn set_syntaxes(&mut self, syntaxes: Vec<Rc<Syntax>>) {
+        self.syntaxes = syntaxes;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.needs_redraw = true;
+    }
+
+    pub fn set_line_numbers(&mut self, line_numbers: LineNumbers) {
+        self.line_numbers = line_numbers;
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    pub fn set_soft_wrap(&mut self, soft_wrap: bool) {
+        self.soft_wrap = soft_wrap;
+        self.scroll_offset.col = 0;
+        self.scroll_wrap_row = 0;
+        self.sticky_col = None;
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    pub fn set_indentation_settings(&mut self, indentation_settings: IndentationSettings) {
+        self.indentation_settings = indentation_settings;
+        self.detect_indentation();
+    }
+
+    //uses the indentation of the buffer's content, or the one configured for its file type
+    fn detect_indentation(&mut self) {
+        let file_type = self.buffer.file_info.file_type.as_deref();
+        let configured = self.indentation_settings.get(file_type);
+        self.buffer.detect_indentation(configured);
+    }
+
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width;
+        self.sticky_col = None;
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //returns whether long lines are wrapped now
+    pub fn toggle_soft_wrap(&mut self) -> bool {
+        self.set_soft_wrap(!self.soft_wrap);
+        self.soft_wrap
+    }
+
+    pub fn set_readonly(&mut self, is_readonly: bool) {
+        self.is_readonly = is_readonly;
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.is_readonly
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.buffer.is_modified
+    }
+
+    pub fn is_browsing(&self) -> bool {
+        self.buffer.is_directory()
+    }
+
+    //opens the file or descends into the directory under the caret
+    pub fn open_selected_entry(&mut self) -> Result<(), Error> {
+        let Some(path) = self.buffer.get_entry_path(self.text_location.line_index) else {
+            return Ok(());
+        };
+        self.load(&path.to_string_lossy())
+    }
+
+    pub fn get_selected_entry_name(&self) -> Option<String> {
+        if !self.is_browsing() {
+            return None;
+        }
+        self.buffer.get_entry_name(self.text_location.line_index)
+    }
+
+    pub fn create_file(&mut self, name: &str) -> Result<(), Error> {
+        if self.is_readonly {
+            return Err(Error::from(ErrorKind::PermissionDenied));
+        }
+        self.buffer.create_file(name)?;
+        self.reload_directory()?;
+        self.select_entry(name);
+        Ok(())
+    }
+
+    pub fn rename_selected_entry(&mut self, new_name: &str) -> Result<(), Error> {
+        if self.is_readonly {
+            return Err(Error::from(ErrorKind::PermissionDenied));
+        }
+        self.buffer
+            .rename_entry(self.text_location.line_index, new_name)?;
+        self.reload_directory()?;
+        self.select_entry(new_name);
+        Ok(())
+    }
+
+    pub fn remove_selected_entry(&mut self) -> Result<(), Error> {
+        if self.is_readonly {
+            return Err(Error::from(ErrorKind::PermissionDenied));
+        }
+        self.buffer.remove_entry(self.text_location.line_index)?;
+        let Location { line_index, .. } = self.text_location;
+        self.reload_directory()?;
+        self.text_location.line_index = min(line_index, self.buffer.get_size().saturating_sub(1));
+        self.scroll_location_into_view();
+        Ok(())
+    }
+
+    fn reload_directory(&mut self) -> Result<(), Error> {
+        let Some(path) = self.buffer.file_info.path.clone() else {
+            return Ok(());
+        };
+        self.buffer = Buffer::load_directory(&path)?;
+        self.text_location = Location::default();
+        self.needs_redraw = true;
+        Ok(())
+    }
+
+    fn select_entry(&mut self, name: &str) {
+        let line_index = (0..self.buffer.get_size())
+            .find(|&index| self.buffer.get_entry_name(index).as_deref() == Some(name))
+            .unwrap_or(0);
+        self.text_location = Location {
+            grapheme_index: 0,
+            line_index,
+        };
+        self.scroll_location_into_view();
+    }
+
     pub fn handle_edit_command(&mut self, command: EditCommand) {
+        if self.is_browsing() || self.is_readonly {
+            return;
+        }
+        let is_multiline_selection = self
+            .get_selection()
+            .is_some_and(|(start, end)| start.line_index != end.line_index);
+        match command {
+            EditCommand::Indent | EditCommand::Dedent if is_multiline_selection => {
+                self.indent_selected_lines(matches!(command, EditCommand::Indent));
+                return;
+            }
+            EditCommand::Dedent => {
+                self.dedent();
+                return;
+            }
+            _ => {}
+        }
+        let deleted_selection = self.delete_selection();
         match command {
-            EditCommand::Insert(c) => self.insert_char(c),
+            EditCommand::Insert(c) => {
+                self.dedent_before_closing_bracket(c);
+                self.insert_char(c);
+            }
+            EditCommand::Delete
+            | EditCommand::Backspace
+            | EditCommand::DeleteWord
+            | EditCommand::BackspaceWord
+                if deleted_selection => {}
             EditCommand::Delete => self.delete(),
             EditCommand::Backspace => self.backspace(),
+            EditCommand::DeleteWord => {
+                self.delete_to(self.get_next_word_location(self.text_location));
+            }
+            EditCommand::BackspaceWord => {
+                self.delete_to(self.get_previous_word_location(self.text_location));
+            }
             EditCommand::Enter => self.enter(),
+            EditCommand::Indent => self.indent(),
+            EditCommand::Dedent => {}
+        }
+    }
+
+    pub fn handle_line_command(&mut self, command: LineCommand) {
+        if self.is_browsing() || self.is_readonly || self.buffer.is_empty() {
+            return;
+        }
+        match command {
+            LineCommand::Duplicate => self.duplicate(),
+            LineCommand::MoveUp => self.move_lines_up(),
+            LineCommand::MoveDown => self.move_lines_down(),
+            LineCommand::Delete => self.delete_lines(),
+            LineCommand::Join => self.join_lines(),
+        }
+        let line_index = min(
+            self.text_location.line_index,
+            self.buffer.get_size().saturating_sub(1),
+        );
+        self.text_location = Location {
+            grapheme_index: min(
+                self.text_location.grapheme_index,
+                self.buffer.get_line_length(line_index),
+            ),
+            line_index,
+        };
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //returns the lines touched by the selection, or the caret's line; a selection ending at
+    //the start of a line does not include that line
+    fn get_selected_lines(&self) -> Range<usize> {
+        let (first, last) = match self.get_selection() {
+            Some((start, end)) if end.grapheme_index == 0 && end.line_index > start.line_index => {
+                (start.line_index, end.line_index.saturating_sub(1))
+            }
+            Some((start, end)) => (start.line_index, end.line_index),
+            None => (self.text_location.line_index, self.text_location.line_index),
+        };
+        first..min(last.saturating_add(1), self.buffer.get_size())
+    }
+
+    //duplicates the selected text after itself, or the caret's line below itself
+    fn duplicate(&mut self) {
+        if let Some((start, end)) = self.get_selection() {
+            let text = self.buffer.get_text(start, end);
+            let copy_end = self.buffer.insert_text(end, &text);
+            self.selection_anchor = Some(end);
+            self.text_location = copy_end;
+        } else {
+            let lines = self.get_selected_lines();
+            self.text_location.line_index =
+                self.text_location.line_index.saturating_add(lines.len());
+            self.buffer.duplicate_lines(lines);
+        }
+    }
+
+    fn move_lines_up(&mut self) {
+        let lines = self.get_selected_lines();
+        let Some(line_above_index) = lines.start.checked_sub(1) else {
+            return;
+        };
+        self.buffer
+            .move_line(line_above_index, lines.end.saturating_sub(1));
+        self.shift_selected_lines(false);
+    }
+
+    fn move_lines_down(&mut self) {
+        let lines = self.get_selected_lines();
+        if lines.end >= self.buffer.get_size() {
+            return;
+        }
+        self.buffer.move_line(lines.end, lines.start);
+        self.shift_selected_lines(true);
+    }
+
+    //keeps the caret and selection on the lines they were on after those lines moved by one
+    fn shift_selected_lines(&mut self, down: bool) {
+        let shift = |location: &mut Location| {
+            location.line_index = if down {
+                location.line_index.saturating_add(1)
+            } else {
+                location.line_index.saturating_sub(1)
+            };
+        };
+        shift(&mut self.text_location);
+        if let Some(anchor) = self.selection_anchor.as_mut() {
+            shift(anchor);
+        }
+    }
+
+    fn delete_lines(&mut self) {
+        let lines = self.get_selected_lines();
+        self.selection_anchor = None;
+        self.text_location.line_index = lines.start;
+        self.buffer.remove_lines(lines);
+    }
+
+    //joins the selected lines, or the caret's line and the next one
+    fn join_lines(&mut self) {
+        let lines = self.get_selected_lines();
+        self.selection_anchor = None;
+        let joins = lines.len().saturating_sub(1).max(1);
+        for _ in 0..joins {
+            if let Some(join_index) = self.buffer.join_lines(lines.start) {
+                self.text_location = Location {
+                    grapheme_index: join_index,
+                    line_index: lines.start,
+                };
+            }
         }
     }
 
@@ -61,36 +416,287 @@ impl View {
             return;
         }
         let Size { width, height } = self.size;
-        if width <= 0 || height <= 0 {
+        if width == 0 {
             return;
         }
         #[allow(clippy::integer_division)]
         let vertical_center = height / 3;
         let top = self.scroll_offset.row;
-        for r in 0..height - 2 {
-            if let Some(line) = self.buffer.lines.get(r.saturating_add(top)) {
-                let _ = Self::render_line(
-                    r,
-                    &line.get_visible_graphemes(
-                        self.scroll_offset.col..self.scroll_offset.col.saturating_add(width),
-                    ),
-                );
+        let text_width = self.get_text_width();
+        self.gutter_caret_line_index = self.text_location.line_index;
+        self.buffer.highlight(top.saturating_add(height));
+        let mut visual_row = self.get_top_visual_row();
+        for r in 0..height {
+            let (line_index, row) = visual_row;
+            if let Some(line) = self.buffer.lines.get(line_index) {
+                let mut line_text = if row == 0 {
+                    self.build_gutter(line_index)
+                } else {
+                    " ".repeat(self.get_gutter_width())
+                };
+                let cols = if self.is_wrapping() {
+                    let starts = line.get_wrap_starts(text_width, self.tab_width);
+                    let start_col = line
+                        .get_previous_width(starts.get(row).copied().unwrap_or(0), self.tab_width);
+                    let end_col = starts.get(row.saturating_add(1)).map_or(
+                        line.get_previous_width(line.graphemes_len(), self.tab_width),
+                        |&end| line.get_previous_width(end, self.tab_width),
+                    );
+                    start_col..min(end_col, start_col.saturating_add(text_width))
+                } else {
+                    self.scroll_offset.col..self.scroll_offset.col.saturating_add(text_width)
+                };
+                line_text.push_str(&line.get_visible_graphemes(
+                    cols,
+                    self.buffer.get_highlights(line_index),
+                    self.get_selected_graphemes(line_index).as_ref(),
+                    &self.theme,
+                    self.tab_width,
+                ));
+                self.render_line(r, &line_text);
+                visual_row = match self.step_visual_rows(visual_row, true, 1) {
+                    next if next == visual_row => (line_index.saturating_add(1), 0),
+                    next => next,
+                };
             } else if r == vertical_center && self.buffer.is_empty() {
-                let _ = Self::render_line(r, &Self::build_welcome_msg(width));
+                self.render_line(r, &Self::build_welcome_msg(width));
             } else {
-                let _ = Self::render_line(r, &"~".to_string());
+                self.render_line(r, "~");
             }
         }
         self.needs_redraw = false;
     }
 
+    //extends the selection, anchoring it at the caret if there is none yet
+    pub fn select_text_location(&mut self, direction: Direction) {
+        if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.text_location);
+        }
+        self.update_text_location(direction);
+        self.needs_redraw = true;
+    }
+
     pub fn move_text_location(&mut self, direction: Direction) {
+        self.clear_selection();
+        self.update_text_location(direction);
+    }
+
+    pub fn clear_selection(&mut self) {
+        if self.selection_anchor.take().is_some() {
+            self.needs_redraw = true;
+        }
+    }
+
+    //returns the selection as ordered start and end locations, if it is not empty
+    fn get_selection(&self) -> Option<(Location, Location)> {
+        let anchor = self.selection_anchor?;
+        let caret = self.text_location;
+        match anchor.cmp(&caret) {
+            Ordering::Less => Some((anchor, caret)),
+            Ordering::Greater => Some((caret, anchor)),
+            Ordering::Equal => None,
+        }
+    }
+
+    //returns the range of selected graphemes on the given line
+    fn get_selected_graphemes(&self, line_index: usize) -> Option<Range<usize>> {
+        let (start, end) = self.get_selection()?;
+        if line_index < start.line_index || line_index > end.line_index {
+            return None;
+        }
+        let from = if line_index == start.line_index {
+            start.grapheme_index
+        } else {
+            0
+        };
+        let to = if line_index == end.line_index {
+            end.grapheme_index
+        } else {
+            self.buffer.get_line_length(line_index)
+        };
+        Some(from..to)
+    }
+
+    //deletes the selected text and places the caret at its start; returns whether there was any
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.get_selection();
+        self.selection_anchor = None;
+        let Some((start, end)) = selection else {
+            return false;
+        };
+        self.buffer.delete_range(start, end);
+        self.text_location = start;
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+        true
+    }
+
+    pub fn handle_mouse_command(&mut self, command: MouseCommand) {
+        match command {
+            MouseCommand::Press(position) => self.press(position),
+            MouseCommand::Drag(position) => self.drag(position),
+            MouseCommand::ScrollUp | MouseCommand::ScrollDown if self.is_wrapping() => {
+                let down = matches!(command, MouseCommand::ScrollDown);
+                (self.scroll_offset.row, self.scroll_wrap_row) =
+                    self.step_visual_rows(self.get_top_visual_row(), down, SCROLL_LINES);
+                self.needs_redraw = true;
+            }
+            MouseCommand::ScrollUp => {
+                self.scroll_offset.row = self.scroll_offset.row.saturating_sub(SCROLL_LINES);
+                self.needs_redraw = true;
+            }
+            MouseCommand::ScrollDown => {
+                let last_line_index = self.buffer.get_size().saturating_sub(1);
+                self.scroll_offset.row = min(
+                    self.scroll_offset.row.saturating_add(SCROLL_LINES),
+                    last_line_index,
+                );
+                self.needs_redraw = true;
+            }
+        }
+    }
+
+    //places the caret at the clicked grapheme, or selects the clicked word on a double click
+    fn press(&mut self, position: Position) {
+        if position.row >= self.size.height {
+            return;
+        }
+        let location = self.position_to_location(position);
+        let is_double_click = self.last_click.is_some_and(|(time, last_location)| {
+            time.elapsed() < DOUBLE_CLICK_DURATION && last_location == location
+        });
+        self.clear_selection();
+        if is_double_click {
+            if let Some(line) = self.buffer.lines.get(location.line_index) {
+                let word = line.get_word_range_at(location.grapheme_index);
+                self.selection_anchor = Some(Location {
+                    grapheme_index: word.start,
+                    line_index: location.line_index,
+                });
+                self.text_location = Location {
+                    grapheme_index: word.end,
+                    line_index: location.line_index,
+                };
+                self.needs_redraw = true;
+            }
+            self.last_click = None;
+        } else {
+            self.text_location = location;
+            self.last_click = Some((Instant::now(), location));
+        }
+        self.scroll_location_into_view();
+    }
+
+    //extends the selection from where the mouse was pressed to the dragged position
+    fn drag(&mut self, position: Position) {
+        let position = Position {
+            col: position.col,
+            row: min(position.row, self.size.height.saturating_sub(1)),
+        };
+        if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.text_location);
+        }
+        self.text_location = self.position_to_location(position);
+        self.last_click = None;
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //maps a position on the screen back to the grapheme rendered there
+    fn position_to_location(&self, position: Position) -> Location {
+        let (line_index, row) = if self.is_wrapping() {
+            self.step_visual_rows(self.get_top_visual_row(), true, position.row)
+        } else {
+            let line_index = min(
+                position.row.saturating_add(self.scroll_offset.row),
+                self.buffer.get_size().saturating_sub(1),
+            );
+            (line_index, 0)
+        };
+        let col = position
+            .col
+            .saturating_sub(self.get_gutter_width())
+            .saturating_add(self.scroll_offset.col);
+        Location {
+            grapheme_index: self.get_grapheme_index_at_col(line_index, row, col),
+            line_index,
+        }
+    }
+
+    //whether scrolling with the mouse has moved the caret's line out of the view
+    pub fn is_caret_visible(&self) -> bool {
+        if self.is_wrapping() {
+            return self.get_caret_row().is_some();
+        }
+        let row = self.text_location.line_index;
+        row >= self.scroll_offset.row
+            && row < self.scroll_offset.row.saturating_add(self.size.height)
+    }
+
+    pub fn get_selected_text(&self) -> Option<String> {
+        let (start, end) = self.get_selection()?;
+        Some(self.buffer.get_text(start, end))
+    }
+
+    //returns the text of the caret's line including its line break
+    pub fn get_current_line_text(&self) -> Option<String> {
+        let line = self.buffer.lines.get(self.text_location.line_index)?;
+        Some(format!("{}\n", line.line_to_string()))
+    }
+
+    //removes the selection, or the caret's line if nothing is selected
+    pub fn cut(&mut self) {
+        if self.is_browsing() || self.is_readonly || self.delete_selection() {
+            return;
+        }
+        let line_index = self.text_location.line_index;
+        self.buffer
+            .remove_lines(line_index..line_index.saturating_add(1));
+        self.text_location = Location {
+            grapheme_index: 0,
+            line_index: min(line_index, self.buffer.get_size().saturating_sub(1)),
+        };
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //inserts the text in place of the selection; whole lines go above the caret's line instead
+    pub fn paste(&mut self, text: &str, is_whole_line: bool) {
+        if self.is_browsing() || self.is_readonly {
+            return;
+        }
+        if !self.delete_selection() && is_whole_line {
+            self.text_location.grapheme_index = 0;
+        }
+        self.insert_text(text);
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        self.text_location = self.buffer.insert_text(self.text_location, text);
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    fn update_text_location(&mut self, direction: Direction) {
         let Location {
             mut grapheme_index,
             mut line_index,
         } = self.text_location;
         let Size { height, width: _ } = self.size;
+        let last_line_index = self.buffer.get_size().saturating_sub(1);
+        let sticky_col = matches!(
+            direction,
+            Direction::Up | Direction::Down | Direction::PageUp | Direction::PageDown
+        )
+        .then(|| self.get_sticky_col());
+        let mut wrap_row = 0;
         match direction {
+            //while wrapping, the caret moves by visual rows and leaves the last one at its end
+            Direction::Up | Direction::Down | Direction::PageUp | Direction::PageDown
+                if self.is_wrapping() =>
+            {
+                (line_index, wrap_row) = self.step_caret_rows(direction);
+            }
             Direction::Up => {
                 line_index = line_index.saturating_sub(1);
             }
@@ -104,13 +710,23 @@ impl View {
                 grapheme_index = grapheme_index.saturating_add(1);
             }
             Direction::PageUp => {
-                line_index = line_index.saturating_sub(height).saturating_sub(1);
+                line_index = line_index.saturating_sub(height);
             }
             Direction::PageDown => {
-                line_index = line_index.saturating_add(height).saturating_sub(1);
+                line_index = line_index.saturating_add(height);
             }
+            //the first press goes to the indentation's end, a second one to the line's start
             Direction::Home => {
-                grapheme_index = 0;
+                let indentation_len = self
+                    .buffer
+                    .lines
+                    .get(line_index)
+                    .map_or(0, Line::get_indentation_len);
+                grapheme_index = if grapheme_index == indentation_len {
+                    0
+                } else {
+                    indentation_len
+                };
             }
             Direction::End => {
                 grapheme_index = match self.buffer.lines.get(line_index) {
@@ -119,22 +735,333 @@ impl View {
                 };
                 //x = self.buffer.lines.get(y).map_or(0, Line::len);
             }
+            Direction::WordLeft => {
+                Location {
+                    grapheme_index,
+                    line_index,
+                } = self.get_previous_word_location(self.text_location);
+            }
+            Direction::WordRight => {
+                Location {
+                    grapheme_index,
+                    line_index,
+                } = self.get_next_word_location(self.text_location);
+            }
+            Direction::ParagraphUp => {
+                line_index = self.get_previous_paragraph_line(line_index);
+                grapheme_index = 0;
+            }
+            Direction::ParagraphDown => {
+                line_index = self.get_next_paragraph_line(line_index);
+                grapheme_index = if self.buffer.is_blank_line(line_index) {
+                    0
+                } else {
+                    self.buffer.get_line_length(line_index)
+                };
+            }
+            Direction::DocumentStart => {
+                line_index = 0;
+                grapheme_index = 0;
+            }
+            Direction::DocumentEnd => {
+                line_index = last_line_index;
+                grapheme_index = self.buffer.get_line_length(last_line_index);
+            }
         }
-        grapheme_index = match self.buffer.lines.get(line_index) {
-            Some(line) => min(line.graphemes_len(), grapheme_index),
-            None => 0,
+        //moving past the last line goes to its end, as there is no line after it to enter
+        if line_index > last_line_index {
+            line_index = last_line_index;
+            grapheme_index = self.buffer.get_line_length(last_line_index);
+        } else if let Some(col) = sticky_col {
+            grapheme_index = self.get_grapheme_index_at_col(line_index, wrap_row, col);
+        }
+        grapheme_index = min(grapheme_index, self.buffer.get_line_length(line_index));
+        self.text_location = Location {
+            grapheme_index,
+            line_index,
         };
-        line_index = min(line_index, self.buffer.get_size());
+        self.sticky_col = sticky_col.map(|col| (col, self.text_location));
+        self.scroll_location_into_view();
+    }
+
+    //returns the column to keep when moving vertically: the one remembered from the last
+    //vertical move, or the caret's current rendered column
+    fn get_sticky_col(&self) -> usize {
+        match self.sticky_col {
+            Some((col, location)) if location == self.text_location => col,
+            _ => self.text_location_to_position().col,
+        }
+    }
+
+    //returns the grapheme rendered at the given column of a line's visual row, or the row's end
+    fn get_grapheme_index_at_col(&self, line_index: usize, row: usize, col: usize) -> usize {
+        let Some(line) = self.buffer.lines.get(line_index) else {
+            return 0;
+        };
+        if !self.is_wrapping() {
+            return line.get_grapheme_index_at(col, self.tab_width);
+        }
+        let starts = line.get_wrap_starts(self.get_text_width(), self.tab_width);
+        let start_col =
+            line.get_previous_width(starts.get(row).copied().unwrap_or(0), self.tab_width);
+        let grapheme_index =
+            line.get_grapheme_index_at(start_col.saturating_add(col), self.tab_width);
+        //the caret before the next row's first grapheme is shown on that row
+        starts
+            .get(row.saturating_add(1))
+            .map_or(grapheme_index, |&next_start| {
+                min(grapheme_index, next_start.saturating_sub(1))
+            })
+    }
+
+    fn is_wrapping(&self) -> bool {
+        self.soft_wrap && !self.is_browsing()
+    }
+
+    //returns the graphemes starting each visual row of a line, which is a single row unless
+    //wrapping
+    fn get_wrap_starts(&self, line_index: usize) -> Vec<usize> {
+        if !self.is_wrapping() {
+            return vec![0];
+        }
+        self.buffer.lines.get(line_index).map_or_else(
+            || vec![0],
+            |line| line.get_wrap_starts(self.get_text_width(), self.tab_width),
+        )
+    }
+
+    //returns the visual row of its line a location is shown on
+    fn get_wrap_row(&self, location: Location) -> usize {
+        self.get_wrap_starts(location.line_index)
+            .partition_point(|&start| start <= location.grapheme_index)
+            .saturating_sub(1)
+    }
+
+    //returns the line and visual row at the top of the view; visual rows are all 0 unless wrapping
+    fn get_top_visual_row(&self) -> (usize, usize) {
+        let line_index = self.scroll_offset.row;
+        let last_row = self.get_wrap_starts(line_index).len().saturating_sub(1);
+        (line_index, min(self.scroll_wrap_row, last_row))
+    }
+
+    //returns the line and visual row a row, or a page for PageUp and PageDown, above or below
+    //the caret's; moving down from the last row leaves the document, which moves the caret to
+    //its end
+    fn step_caret_rows(&self, direction: Direction) -> (usize, usize) {
+        let down = matches!(direction, Direction::Down | Direction::PageDown);
+        let count = match direction {
+            Direction::PageUp | Direction::PageDown => self.size.height,
+            _ => 1,
+        };
+        let line_index = self.text_location.line_index;
+        let from = (line_index, self.get_wrap_row(self.text_location));
+        match self.step_visual_rows(from, down, count) {
+            to if to == from && down => (line_index.saturating_add(1), 0),
+            to => to,
+        }
+    }
+
+    //returns the line and visual row the given number of visual rows below or above another,
+    //stopping at the first and last row of the document
+    fn step_visual_rows(&self, from: (usize, usize), down: bool, count: usize) -> (usize, usize) {
+        let (mut line_index, mut row) = from;
+        let last_line_index = self.buffer.get_size().saturating_sub(1);
+        for _ in 0..count {
+            if down {
+                if row.saturating_add(1) < self.get_wrap_starts(line_index).len() {
+                    row = row.saturating_add(1);
+                } else if line_index < last_line_index {
+                    line_index = line_index.saturating_add(1);
+                    row = 0;
+                } else {
+                    break;
+                }
+            } else if row > 0 {
+                row = row.saturating_sub(1);
+            } else if line_index > 0 {
+                line_index = line_index.saturating_sub(1);
+                row = self.get_wrap_starts(line_index).len().saturating_sub(1);
+            } else {
+                break;
+            }
+        }
+        (line_index, row)
+    }
+
+    //returns the screen row the caret is on while wrapping, if it is within the view
+    fn get_caret_row(&self) -> Option<usize> {
+        let top = self.get_top_visual_row();
+        let caret = (
+            self.text_location.line_index,
+            self.get_wrap_row(self.text_location),
+        );
+        if caret < top {
+            return None;
+        }
+        let mut rows: usize = 0;
+        let (mut line_index, mut row) = top;
+        while line_index < caret.0 {
+            rows = self
+                .get_wrap_starts(line_index)
+                .len()
+                .saturating_sub(row)
+                .saturating_add(rows);
+            if rows >= self.size.height {
+                return None;
+            }
+            line_index = line_index.saturating_add(1);
+            row = 0;
+        }
+        let rows = rows.saturating_add(caret.1.saturating_sub(row));
+        (rows < self.size.height).then_some(rows)
+    }
+
+    //returns the blank line before the paragraph above the given line, or the first line
+    fn get_previous_paragraph_line(&self, line_index: usize) -> usize {
+        (0..line_index)
+            .rev()
+            .skip_while(|&index| self.buffer.is_blank_line(index))
+            .find(|&index| self.buffer.is_blank_line(index))
+            .unwrap_or(0)
+    }
+
+    //returns the blank line after the paragraph below the given line, or the last line
+    fn get_next_paragraph_line(&self, line_index: usize) -> usize {
+        let last_line_index = self.buffer.get_size().saturating_sub(1);
+        (line_index.saturating_add(1)..=last_line_index)
+            .skip_while(|&index| self.buffer.is_blank_line(index))
+            .find(|&index| self.buffer.is_blank_line(index))
+            .unwrap_or(last_line_index)
+    }
+
+    //returns the start of the previous word, or the end of the previous line at a line's start
+    fn get_previous_word_location(&self, location: Location) -> Location {
+        let Location {
+            grapheme_index,
+            line_index,
+        } = location;
+        if grapheme_index > 0 {
+            return Location {
+                grapheme_index: self
+                    .buffer
+                    .lines
+                    .get(line_index)
+                    .map_or(0, |line| line.get_previous_word_start(grapheme_index)),
+                line_index,
+            };
+        }
+        match line_index.checked_sub(1) {
+            Some(previous_line_index) => Location {
+                grapheme_index: self.buffer.get_line_length(previous_line_index),
+                line_index: previous_line_index,
+            },
+            None => location,
+        }
+    }
+
+    //returns the end of the next word, or the start of the next line at a line's end
+    fn get_next_word_location(&self, location: Location) -> Location {
+        let Location {
+            grapheme_index,
+            line_index,
+        } = location;
+        if grapheme_index < self.buffer.get_line_length(line_index) {
+            return Location {
+                grapheme_index: self.buffer.lines[line_index].get_next_word_end(grapheme_index),
+                line_index,
+            };
+        }
+        let next_line_index = line_index.saturating_add(1);
+        if next_line_index < self.buffer.get_size() {
+            Location {
+                grapheme_index: 0,
+                line_index: next_line_index,
+            }
+        } else {
+            location
+        }
+    }
+
+    //deletes the text between the caret and the given location
+    fn delete_to(&mut self, location: Location) {
+        let start = min(self.text_location, location);
+        let end = self.text_location.max(location);
+        if start == end {
+            return;
+        }
+        self.buffer.delete_range(start, end);
+        self.text_location = start;
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //moves the caret to the given 0-based line and grapheme, centering that line vertically
+    pub fn go_to(&mut self, line_index: usize, grapheme_index: usize) {
+        let line_index = min(line_index, self.buffer.get_size().saturating_sub(1));
+        let grapheme_index = min(grapheme_index, self.buffer.get_line_length(line_index));
         self.text_location = Location {
             grapheme_index,
             line_index,
         };
+        #[allow(clippy::integer_division)]
+        let half_height = self.size.height / 2;
+        self.scroll_offset.row = line_index.saturating_sub(half_height);
+        self.scroll_wrap_row = 0;
+        self.needs_redraw = true;
         self.scroll_location_into_view();
     }
 
+    //the width of the line numbers left of the text and the space after them, sized to the
+    //number of digits of the last line number
+    fn get_gutter_width(&self) -> usize {
+        if self.line_numbers == LineNumbers::Off || self.is_browsing() {
+            return 0;
+        }
+        self.buffer.get_size().to_string().len().saturating_add(1)
+    }
+
+    //the width left for the text, keeping at least one column when the gutter is too wide
+    fn get_text_width(&self) -> usize {
+        self.size
+            .width
+            .saturating_sub(self.get_gutter_width())
+            .max(1)
+    }
+
+    //returns the line's number right-aligned in the gutter, styled for the caret's line or
+    //the others
+    fn build_gutter(&self, line_index: usize) -> String {
+        let gutter_width = self.get_gutter_width();
+        if gutter_width == 0 {
+            return String::new();
+        }
+        let caret_line_index = self.text_location.line_index;
+        let (number, style) = if line_index == caret_line_index {
+            (line_index.saturating_add(1), self.theme.current_line_number)
+        } else if self.line_numbers == LineNumbers::Relative {
+            (
+                line_index.abs_diff(caret_line_index),
+                self.theme.line_number,
+            )
+        } else {
+            (line_index.saturating_add(1), self.theme.line_number)
+        };
+        let number_width = gutter_width.saturating_sub(1);
+        format!(
+            "{}{number:>number_width$}{} ",
+            Terminal::style_sequence(&style.over(self.theme.text)),
+            Terminal::style_sequence(&self.theme.text)
+        )
+    }
+
     //updates scroll_offset when scrolling
     fn scroll_location_into_view(&mut self) {
-        let Size { height, width } = self.size;
+        if self.is_wrapping() {
+            self.scroll_wrapped_location_into_view();
+            return;
+        }
+        let height = self.size.height;
+        let width = self.get_text_width();
         //convert text location to a position on the grid
         let Position { col, row } = self.text_location_to_position();
         let mut offset_changed = false;
@@ -147,7 +1074,7 @@ impl View {
             scroll_offset_y = row;
             offset_changed = true;
         } else if row >= scroll_offset_y.saturating_add(height) {
-            scroll_offset_y = scroll_offset_y.saturating_sub(height).saturating_add(1);
+            scroll_offset_y = row.saturating_sub(height).saturating_add(1);
             offset_changed = true;
         }
 
@@ -158,13 +1085,36 @@ impl View {
             scroll_offset_x = col.saturating_sub(width).saturating_add(1);
             offset_changed = true;
         }
-        self.needs_redraw = offset_changed;
+        let gutter_changed = self.get_gutter_width() > 0
+            && self.gutter_caret_line_index != self.text_location.line_index;
+        self.needs_redraw = self.needs_redraw || offset_changed || gutter_changed;
         self.scroll_offset = Position {
             col: scroll_offset_x,
             row: scroll_offset_y,
         };
     }
 
+    //scrolls by visual rows until the caret's row is within the view
+    fn scroll_wrapped_location_into_view(&mut self) {
+        let top = self.get_top_visual_row();
+        let caret = (
+            self.text_location.line_index,
+            self.get_wrap_row(self.text_location),
+        );
+        let new_top = if caret < top {
+            caret
+        } else if self.get_caret_row().is_some() {
+            top
+        } else {
+            self.step_visual_rows(caret, false, self.size.height.saturating_sub(1))
+        };
+        let gutter_changed = self.get_gutter_width() > 0
+            && self.gutter_caret_line_index != self.text_location.line_index;
+        self.needs_redraw = self.needs_redraw || new_top != top || gutter_changed;
+        (self.scroll_offset.row, self.scroll_wrap_row) = new_top;
+        self.scroll_offset.col = 0;
+    }
+
     fn build_welcome_msg(width: usize) -> String {
         if width == 0 {
             return String::new();
@@ -187,14 +1137,22 @@ impl View {
         self.needs_redraw = true;
     }
 
-    fn render_line(at: usize, line_text: &str) {
-        let result = Terminal::print_row(at, line_text);
+    fn render_line(&self, at: usize, line_text: &str) {
+        let result = Terminal::print_styled_row(at, line_text, &self.theme.text);
         debug_assert!(result.is_ok(), "Failed to render line.");
     }
 
     pub fn get_caret_position(&self) -> Position {
-        self.text_location_to_position()
-            .subtract(self.scroll_offset)
+        let Position { col, mut row } = self
+            .text_location_to_position()
+            .subtract(self.scroll_offset);
+        if self.is_wrapping() {
+            row = self.get_caret_row().unwrap_or(0);
+        }
+        Position {
+            col: col.saturating_add(self.get_gutter_width()),
+            row,
+        }
     }
 
     fn text_location_to_position(&self) -> Position {
@@ -202,16 +1160,110 @@ impl View {
             grapheme_index,
             line_index,
         } = self.text_location;
-        let total_width = match self.buffer.lines.get(line_index) {
-            Some(line) => line.get_previous_width(grapheme_index),
+        let mut total_width = match self.buffer.lines.get(line_index) {
+            Some(line) => line.get_previous_width(grapheme_index, self.tab_width),
             None => 0,
         };
+        //while wrapping, the column is within the caret's visual row, and whitespace hanging
+        //past the width puts the caret on the row's last column
+        if self.is_wrapping() {
+            if let Some(line) = self.buffer.lines.get(line_index) {
+                let starts = line.get_wrap_starts(self.get_text_width(), self.tab_width);
+                let row = self.get_wrap_row(self.text_location);
+                let start_col =
+                    line.get_previous_width(starts.get(row).copied().unwrap_or(0), self.tab_width);
+                total_width = min(
+                    total_width.saturating_sub(start_col),
+                    self.get_text_width().saturating_sub(1),
+                );
+            }
+        }
         Position {
             row: line_index,
             col: total_width,
         }
     }
 
+    //inserts a tab, or spaces up to the next multiple of the indent width
+    fn indent(&mut self) {
+        let Indentation { expand_tabs, width } = self.buffer.indentation;
+        if !expand_tabs {
+            self.insert_char('\t');
+            return;
+        }
+        let Location {
+            grapheme_index,
+            line_index,
+        } = self.text_location;
+        let col = self.buffer.lines.get(line_index).map_or(0, |line| {
+            line.get_previous_width(grapheme_index, self.tab_width)
+        });
+        let spaces = width.saturating_sub(col.checked_rem(width).unwrap_or(0));
+        self.insert_text(&" ".repeat(spaces));
+    }
+
+    fn dedent(&mut self) {
+        self.dedent_line(self.text_location.line_index);
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //indents or dedents every selected line by one level, keeping the selection on the same
+    //text; blank lines are not indented
+    fn indent_selected_lines(&mut self, indent: bool) {
+        let unit = self.get_indent_unit();
+        let unit_len = unit.chars().count();
+        for line_index in self.get_selected_lines() {
+            if !indent {
+                self.dedent_line(line_index);
+            } else if !self.buffer.is_blank_line(line_index) {
+                let line_start = Location {
+                    grapheme_index: 0,
+                    line_index,
+                };
+                self.buffer.insert_text(line_start, &unit);
+                //a selection from a line's start still starts there
+                self.update_line_locations(line_index, |grapheme_index| {
+                    if grapheme_index > 0 {
+                        grapheme_index.saturating_add(unit_len)
+                    } else {
+                        0
+                    }
+                });
+            }
+        }
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
+    }
+
+    //removes one level of indentation from a line, keeping the caret and selection on the
+    //same text
+    fn dedent_line(&mut self, line_index: usize) {
+        let removed = self
+            .buffer
+            .dedent_line(line_index, self.buffer.indentation.width);
+        if removed > 0 {
+            self.update_line_locations(line_index, |grapheme_index| {
+                grapheme_index.saturating_sub(removed)
+            });
+        }
+    }
+
+    //moves the caret and selection anchor within a line whose graphemes have shifted
+    fn update_line_locations(&mut self, line_index: usize, update: impl Fn(usize) -> usize) {
+        for location in [
+            Some(&mut self.text_location),
+            self.selection_anchor.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if location.line_index == line_index {
+                location.grapheme_index = update(location.grapheme_index);
+            }
+        }
+    }
+
     fn insert_char(&mut self, c: char) {
         let Location {
             mut grapheme_index,
@@ -232,18 +1284,16 @@ impl View {
             grapheme_index,
             line_index,
         } = self.text_location;
-        if line_index != self.buffer.get_size() - 1
-            || grapheme_index < self.buffer.get_line_length(line_index) - 1
-        {
-            if grapheme_index == self.buffer.get_line_length(line_index) - 1 {
-                let next_line_index = line_index.saturating_add(1);
-                self.buffer.merge(line_index, next_line_index);
-            } else {
-                self.buffer.delete(line_index, grapheme_index);
-            }
-            self.scroll_location_into_view();
-            self.needs_redraw = true;
+        let next_line_index = line_index.saturating_add(1);
+        if grapheme_index < self.buffer.get_line_length(line_index) {
+            self.buffer.delete(line_index, grapheme_index);
+        } else if next_line_index < self.buffer.get_size() {
+            self.buffer.merge(line_index, next_line_index);
+        } else {
+            return;
         }
+        self.scroll_location_into_view();
+        self.needs_redraw = true;
     }
 
     fn backspace(&mut self) {
@@ -270,32 +1320,278 @@ impl View {
         }
     }
 
+    //starts a new line indented like the caret's line, or one level deeper after a bracket
+    //opening a block; a bracket closing that block right after the caret goes to a line of
+    //its own
     fn enter(&mut self) {
         let Location {
-            mut grapheme_index,
-            mut line_index,
-        } = self.text_location;
-        self.buffer.enter(line_index, grapheme_index);
-        grapheme_index = 0;
-        line_index = line_index.saturating_add(1);
-        self.text_location = Location {
             grapheme_index,
             line_index,
+        } = self.text_location;
+        let line_start = Location {
+            grapheme_index: 0,
+            line_index,
+        };
+        let line_end = Location {
+            grapheme_index: self.buffer.get_line_length(line_index),
+            line_index,
         };
+        let indentation_len = self
+            .buffer
+            .lines
+            .get(line_index)
+            .map_or(0, Line::get_indentation_len);
+        let after = self.buffer.get_text(self.text_location, line_end);
+        //within the indentation of a line with text, the line moves down with its indentation
+        //as it is, leaving an empty line above it
+        if grapheme_index <= indentation_len && !after.trim().is_empty() {
+            self.buffer.enter(line_index, 0);
+            self.text_location.line_index = line_index.saturating_add(1);
+            self.scroll_location_into_view();
+            self.needs_redraw = true;
+            return;
+        }
+        let indentation_end = Location {
+            grapheme_index: min(grapheme_index, indentation_len),
+            line_index,
+        };
+        let indentation = self.buffer.get_text(line_start, indentation_end);
+        let before = self.buffer.get_text(line_start, self.text_location);
+        let opened = before
+            .trim_end()
+            .chars()
+            .next_back()
+            .filter(|&c| self.opens_block(c));
+        let closes_block = opened.is_some_and(|open| {
+            BLOCK_BRACKETS
+                .iter()
+                .any(|&(bracket, close)| bracket == open && after.trim_start().starts_with(close))
+        });
+        //whitespace around the break would only add to the indentation, or be left trailing
+        let (delete_start, delete_end) = if before.trim().is_empty() && after.trim().is_empty() {
+            (line_start, line_end)
+        } else {
+            let leading_whitespace_len = after.len().saturating_sub(after.trim_start().len());
+            (
+                self.text_location,
+                Location {
+                    grapheme_index: grapheme_index
+                        .saturating_add(after[..leading_whitespace_len].chars().count()),
+                    line_index,
+                },
+            )
+        };
+        if delete_start < delete_end {
+            self.buffer.delete_range(delete_start, delete_end);
+        }
+        self.buffer.enter(line_index, delete_start.grapheme_index);
+        let mut new_indentation = indentation.clone();
+        if opened.is_some() {
+            new_indentation.push_str(&self.get_indent_unit());
+        }
+        let caret = Location {
+            grapheme_index: new_indentation.chars().count(),
+            line_index: line_index.saturating_add(1),
+        };
+        if closes_block {
+            new_indentation.push('\n');
+            new_indentation.push_str(&indentation);
+        }
+        if !new_indentation.is_empty() {
+            self.buffer.insert_text(
+                Location {
+                    grapheme_index: 0,
+                    line_index: caret.line_index,
+                },
+                &new_indentation,
+            );
+        }
+        self.text_location = caret;
         self.scroll_location_into_view();
         self.needs_redraw = true;
     }
 
+    //whether a line ending with the character opens a block in the buffer's file type
+    fn opens_block(&self, c: char) -> bool {
+        BLOCK_BRACKETS.iter().any(|&(open, _)| open == c)
+            || (c == ':'
+                && self
+                    .buffer
+                    .file_info
+                    .file_type
+                    .as_deref()
+                    .is_some_and(|file_type| {
+                        COLON_BLOCK_FILE_TYPES
+                            .iter()
+                            .any(|name| name.eq_ignore_ascii_case(file_type))
+                    }))
+    }
+
+    //returns the text of one level of indentation
+    fn get_indent_unit(&self) -> String {
+        let Indentation { expand_tabs, width } = self.buffer.indentation;
+        if expand_tabs {
+            " ".repeat(width)
+        } else {
+            "\t".to_string()
+        }
+    }
+
+    //a closing bracket typed on a line holding only indentation ends a block, so the line
+    //is dedented first
+    fn dedent_before_closing_bracket(&mut self, c: char) {
+        if BLOCK_BRACKETS.iter().any(|&(_, close)| close == c)
+            && self.buffer.is_blank_line(self.text_location.line_index)
+        {
+            self.dedent();
+        }
+    }
+
+    pub fn has_file_name(&self) -> bool {
+        self.buffer.file_info.path.is_some()
+    }
+
+    pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
+        if self.is_readonly {
+            return Err(Error::from(ErrorKind::PermissionDenied));
+        }
+        self.buffer.save_as(file_name)?;
+        //the new name may change the file type, and with it the configured indentation
+        self.detect_indentation();
+        Ok(())
+    }
+
     pub fn save(&mut self) -> Result<(), Error> {
+        if self.is_readonly {
+            return Err(Error::from(ErrorKind::PermissionDenied));
+        }
         self.buffer.save()
     }
 
     pub fn get_current_document_status(&self) -> DocumentStatus {
+        let is_git_message = self.buffer.file_info.is_git_message();
+        let total_lines = self.buffer.get_size();
+        let num_lines = if is_git_message {
+            self.buffer.get_size_without_comments()
+        } else {
+            total_lines
+        };
+        let line_index = self.text_location.line_index;
         DocumentStatus {
-            num_lines: self.buffer.get_size(),
-            current_caret_line: self.text_location.line_index,
+            num_lines,
+            total_lines,
+            current_caret_line: line_index,
             file_name: format!("{}", self.buffer.file_info),
             is_modified: self.buffer.is_modified,
+            is_readonly: self.is_readonly,
+            current_line_width: self.buffer.lines.get(line_index).map_or(0, |line| {
+                line.get_previous_width(line.graphemes_len(), self.tab_width)
+            }),
+            column_limit: is_git_message.then_some(GIT_MESSAGE_COLUMN_LIMIT),
+            file_type: self.buffer.file_info.file_type.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //presses Enter at the given location, returning the lines and the caret's line and grapheme
+    fn enter_at(
+        text: &str,
+        line_index: usize,
+        grapheme_index: usize,
+    ) -> (Vec<String>, (usize, usize)) {
+        let mut view = View::new(0);
+        view.load_text(text);
+        view.text_location = Location {
+            grapheme_index,
+            line_index,
+        };
+        view.enter();
+        let lines = view.buffer.lines.iter().map(Line::line_to_string).collect();
+        let Location {
+            grapheme_index,
+            line_index,
+        } = view.text_location;
+        (lines, (line_index, grapheme_index))
+    }
+
+    #[test]
+    fn enter_at_line_start_keeps_indentation() {
+        assert_eq!(
+            enter_at("    foo();", 0, 0),
+            (vec![String::new(), "    foo();".to_string()], (1, 0))
+        );
+    }
+
+    #[test]
+    fn enter_within_indentation_keeps_indentation() {
+        assert_eq!(
+            enter_at("    foo();", 0, 2),
+            (vec![String::new(), "    foo();".to_string()], (1, 2))
+        );
+    }
+
+    #[test]
+    fn enter_after_text_indents_like_the_line() {
+        assert_eq!(
+            enter_at("    foo(); bar();", 0, 10),
+            (
+                vec!["    foo();".to_string(), "    bar();".to_string()],
+                (1, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn enter_between_brackets_opens_a_block() {
+        assert_eq!(
+            enter_at("fn f() {}", 0, 8),
+            (
+                vec!["fn f() {".to_string(), "    ".to_string(), "}".to_string()],
+                (1, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn enter_after_colon_opens_a_block_in_python() {
+        assert_eq!(
+            enter_at("#!/usr/bin/env python3\nif x:", 1, 5),
+            (
+                vec![
+                    "#!/usr/bin/env python3".to_string(),
+                    "if x:".to_string(),
+                    "    ".to_string()
+                ],
+                (2, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn enter_after_colon_does_not_indent_in_plain_text() {
+        assert_eq!(
+            enter_at("let x:", 0, 6),
+            (vec!["let x:".to_string(), String::new()], (1, 0))
+        );
+    }
+
+    #[test]
+    fn enter_on_a_blank_line_drops_its_whitespace() {
+        assert_eq!(
+            enter_at("    ", 0, 2),
+            (vec![String::new(), "  ".to_string()], (1, 2))
+        );
+    }
+
+    #[test]
+    fn enter_in_an_empty_buffer_adds_two_lines() {
+        assert_eq!(
+            enter_at("", 0, 0),
+            (vec![String::new(), String::new()], (1, 0))
+        );
+    }
+}
@@ -1,15 +1,32 @@
+use super::highlighter::{Highlighter, Span};
 use super::line::Line;
-use super::FileInfo;
+use super::Location;
+use super::{FileInfo, Indentation, Syntax, MODELINE_SEARCH_LINES};
+use std::cmp::{max, min};
 use std::fs;
 use std::fs::File;
 use std::io::Error;
+use std::io::ErrorKind;
 use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+const PARENT_DIRECTORY_ENTRY: &str = "../";
+
+//indentation is detected from up to this many lines at the start of a file
+const INDENTATION_SEARCH_LINES: usize = 1000;
+const MAX_DETECTED_INDENT_WIDTH: usize = 8;
 
 #[derive(Default)]
 pub struct Buffer {
     pub lines: Vec<Line>,
     pub file_info: FileInfo,
     pub is_modified: bool,
+    highlighter: Highlighter,
+    syntaxes: Vec<Rc<Syntax>>,
+    pub indentation: Indentation,
 }
 
 impl Buffer {
@@ -17,19 +34,150 @@ impl Buffer {
         self.lines.is_empty()
     }
 
-    pub fn load(file_name: &str) -> Result<Self, Error> {
+    pub fn load(file_name: &str, syntaxes: &[Rc<Syntax>]) -> Result<Self, Error> {
         let file_contents = fs::read_to_string(file_name)?;
         let mut lines = Vec::new();
         for line in file_contents.lines() {
             lines.push(Line::from(line));
         }
-        Ok(Self {
+        let mut buffer = Self {
             lines,
             file_info: FileInfo::from(file_name),
-            is_modified: false,
+            syntaxes: syntaxes.to_vec(),
+            ..Self::default()
+        };
+        buffer.detect_file_type();
+        Ok(buffer)
+    }
+
+    pub fn from_text(text: &str, syntaxes: &[Rc<Syntax>]) -> Self {
+        let mut buffer = Self {
+            lines: text.lines().map(Line::from).collect(),
+            syntaxes: syntaxes.to_vec(),
+            ..Self::default()
+        };
+        buffer.detect_file_type();
+        buffer
+    }
+
+    //detects the file type from the file name and the lines which may hold a shebang or
+    //modeline, and highlights the buffer accordingly
+    fn detect_file_type(&mut self) {
+        let tail_start = max(
+            MODELINE_SEARCH_LINES,
+            self.lines.len().saturating_sub(MODELINE_SEARCH_LINES),
+        );
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .take(MODELINE_SEARCH_LINES)
+            .chain(self.lines.iter().skip(tail_start))
+            .map(Line::line_to_string)
+            .collect();
+        self.file_info.detect_file_type(&lines, &self.syntaxes);
+        let syntax = self.file_info.file_type.as_ref().and_then(|file_type| {
+            self.syntaxes
+                .iter()
+                .find(|syntax| syntax.file_type.eq_ignore_ascii_case(file_type))
+        });
+        self.highlighter = Highlighter::new(syntax.cloned());
+    }
+
+    //lists the entries of a directory, one per line: the parent first, then sub-directories, then files
+    pub fn load_directory(path: &Path) -> Result<Self, Error> {
+        let path = fs::canonicalize(path)?;
+        let mut directories = Vec::new();
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() {
+                directories.push(format!("{name}/"));
+            } else {
+                files.push(name);
+            }
+        }
+        directories.sort();
+        files.sort();
+        let mut lines = Vec::new();
+        if path.parent().is_some() {
+            lines.push(Line::from(PARENT_DIRECTORY_ENTRY));
+        }
+        for name in directories.iter().chain(files.iter()) {
+            lines.push(Line::from(name));
+        }
+        Ok(Self {
+            lines,
+            file_info: FileInfo::from_directory(&path),
+            ..Self::default()
         })
     }
 
+    pub fn is_directory(&self) -> bool {
+        self.file_info.is_directory
+    }
+
+    //returns the path of the directory entry listed on the given line
+    pub fn get_entry_path(&self, line_index: usize) -> Option<PathBuf> {
+        if !self.is_directory() {
+            return None;
+        }
+        let directory = self.file_info.path.as_ref()?;
+        let name = self.lines.get(line_index)?.line_to_string();
+        if name == PARENT_DIRECTORY_ENTRY {
+            return directory.parent().map(Path::to_path_buf);
+        }
+        Some(directory.join(name.trim_end_matches('/')))
+    }
+
+    pub fn get_entry_name(&self, line_index: usize) -> Option<String> {
+        let name = self.lines.get(line_index)?.line_to_string();
+        if name == PARENT_DIRECTORY_ENTRY {
+            return None;
+        }
+        Some(name.trim_end_matches('/').to_string())
+    }
+
+    fn get_directory(&self) -> Result<&Path, Error> {
+        match (&self.file_info.path, self.is_directory()) {
+            (Some(path), true) => Ok(path),
+            _ => Err(Error::from(ErrorKind::NotADirectory)),
+        }
+    }
+
+    pub fn create_file(&self, name: &str) -> Result<(), Error> {
+        let path = self.get_directory()?.join(name);
+        File::options().write(true).create_new(true).open(path)?;
+        Ok(())
+    }
+
+    pub fn rename_entry(&self, line_index: usize, new_name: &str) -> Result<(), Error> {
+        let new_path = self.get_directory()?.join(new_name);
+        let Some(old_path) = self
+            .get_entry_name(line_index)
+            .and_then(|_| self.get_entry_path(line_index))
+        else {
+            return Err(Error::from(ErrorKind::NotFound));
+        };
+        if new_path.exists() {
+            return Err(Error::from(ErrorKind::AlreadyExists));
+        }
+        fs::rename(old_path, new_path)
+    }
+
+    pub fn remove_entry(&self, line_index: usize) -> Result<(), Error> {
+        let Some(path) = self
+            .get_entry_name(line_index)
+            .and_then(|_| self.get_entry_path(line_index))
+        else {
+            return Err(Error::from(ErrorKind::NotFound));
+        };
+        if path.is_dir() {
+            return Err(Error::from(ErrorKind::IsADirectory));
+        }
+        fs::remove_file(path)
+    }
+
     pub fn get_size(&self) -> usize {
         if self.is_empty() {
             return 0;
@@ -37,23 +185,31 @@ impl Buffer {
         self.lines.len()
     }
 
+    //counts the lines git keeps from a commit message, skipping `#` comments
+    pub fn get_size_without_comments(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| !line.line_to_string().starts_with('#'))
+            .count()
+    }
+
     pub fn get_line_length(&self, line_index: usize) -> usize {
-        if line_index == self.lines.len() {
-            return 0;
-        }
-        self.lines[line_index].graphemes_len()
+        self.lines.get(line_index).map_or(0, Line::graphemes_len)
+    }
+
+    pub fn is_blank_line(&self, line_index: usize) -> bool {
+        self.lines
+            .get(line_index)
+            .is_some_and(|line| line.line_to_string().trim().is_empty())
     }
 
     pub fn insert_in_line(&mut self, line_index: usize, c: char, grapheme_index: usize) {
         if line_index == self.lines.len() {
             self.lines.push(Line::from(""));
         }
-        match self.lines.get_mut(line_index) {
-            Some(line) => {
-                line.insert(c, grapheme_index);
-                self.is_modified = true;
-            }
-            None => (),
+        if let Some(line) = self.lines.get_mut(line_index) {
+            line.insert(c, grapheme_index);
+            self.mark_modified(line_index);
         }
     }
 
@@ -62,6 +218,11 @@ impl Buffer {
     }
 
     pub fn enter(&mut self, line_index: usize, grapheme_index: usize) {
+        //an empty buffer has no line for the caret yet, but the caret's line is kept above the
+        //new one
+        if line_index == self.lines.len() {
+            self.lines.push(Line::from(""));
+        }
         if grapheme_index == 0 {
             self.insert_new_line(line_index);
         } else {
@@ -77,40 +238,253 @@ impl Buffer {
                 }
             }
         }
-        self.is_modified = true;
+        self.mark_modified(line_index);
     }
 
     pub fn save(&mut self) -> Result<(), Error> {
-        if let Some(path) = &self.file_info.path {
-            let mut file = File::create(path)?;
-            for (_, line) in self.lines.iter().enumerate() {
-                writeln!(file, "{}", &line.line_to_string())?;
-            }
+        if self.is_directory() {
+            return Err(Error::from(ErrorKind::IsADirectory));
+        }
+        let Some(path) = &self.file_info.path else {
+            return Err(Error::from(ErrorKind::NotFound));
+        };
+        let mut file = File::create(path)?;
+        for line in &self.lines {
+            writeln!(file, "{}", &line.line_to_string())?;
         }
         self.is_modified = false;
         Ok(())
     }
 
+    //saves to a new file name, which the buffer keeps only if writing succeeds
+    pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
+        let file_info = std::mem::replace(&mut self.file_info, FileInfo::from(file_name));
+        let result = self.save();
+        match result {
+            Ok(()) => self.detect_file_type(),
+            Err(_) => self.file_info = file_info,
+        }
+        result
+    }
+
     pub fn delete(&mut self, line_index: usize, grapheme_index: usize) {
-        if line_index < self.lines.len() {
-            match self.lines.get_mut(line_index) {
-                Some(line) => {
-                    line.delete(grapheme_index);
-                    self.is_modified = true;
-                }
-                None => (),
+        if let Some(line) = self.lines.get_mut(line_index) {
+            line.delete(grapheme_index);
+            self.mark_modified(line_index);
+        }
+    }
+
+    //returns the text from start up to, but not including, end
+    pub fn get_text(&self, start: Location, end: Location) -> String {
+        let mut result = String::new();
+        for line_index in start.line_index..=end.line_index {
+            let Some(line) = self.lines.get(line_index) else {
+                break;
+            };
+            let text = line.line_to_string();
+            let from = if line_index == start.line_index {
+                start.grapheme_index
+            } else {
+                0
+            };
+            if line_index == end.line_index {
+                let to = end.grapheme_index.saturating_sub(from);
+                result.extend(text.graphemes(true).skip(from).take(to));
+            } else {
+                result.extend(text.graphemes(true).skip(from));
+                result.push('\n');
             }
         }
+        result
+    }
+
+    //inserts text which may span several lines, returning the location right after it
+    pub fn insert_text(&mut self, at: Location, text: &str) -> Location {
+        if at.line_index >= self.lines.len() {
+            self.lines.push(Line::default());
+        }
+        let line_index = min(at.line_index, self.lines.len().saturating_sub(1));
+        let tail = self.lines[line_index].split(at.grapheme_index);
+        let mut end = Location {
+            grapheme_index: 0,
+            line_index,
+        };
+        for (index, segment) in text.split('\n').enumerate() {
+            let segment = segment.strip_suffix('\r').unwrap_or(segment);
+            if index > 0 {
+                end.line_index = end.line_index.saturating_add(1);
+                self.lines.insert(end.line_index, Line::default());
+            }
+            let line = &mut self.lines[end.line_index];
+            line.append(&Line::from(segment));
+            end.grapheme_index = line.graphemes_len();
+        }
+        self.lines[end.line_index].append(&tail);
+        self.mark_modified(line_index);
+        end
+    }
+
+    pub fn remove_lines(&mut self, lines: Range<usize>) {
+        let end = min(lines.end, self.lines.len());
+        if lines.start < end {
+            self.lines.drain(lines.start..end);
+            self.mark_modified(lines.start);
+        }
+    }
+
+    //inserts copies of the given lines right after them
+    pub fn duplicate_lines(&mut self, lines: Range<usize>) {
+        let end = min(lines.end, self.lines.len());
+        let copies: Vec<Line> = self.lines[lines.start..end].to_vec();
+        self.lines.splice(end..end, copies);
+        self.mark_modified(end);
+    }
+
+    pub fn move_line(&mut self, from_index: usize, to_index: usize) {
+        if from_index < self.lines.len() && to_index < self.lines.len() {
+            let line = self.lines.remove(from_index);
+            self.lines.insert(to_index, line);
+            self.mark_modified(min(from_index, to_index));
+        }
+    }
+
+    //appends the next line to the given one, separated by a single space instead of the
+    //whitespace around the line break; returns the grapheme index where they were joined
+    pub fn join_lines(&mut self, line_index: usize) -> Option<usize> {
+        let next_line_index = line_index.saturating_add(1);
+        if next_line_index >= self.lines.len() {
+            return None;
+        }
+        let indentation_len = self.lines[next_line_index].get_indentation_len();
+        self.lines[next_line_index] = self.lines[next_line_index].split(indentation_len);
+        let line = &mut self.lines[line_index];
+        line.split(line.get_trailing_whitespace_start());
+        let join_index = line.graphemes_len();
+        if join_index > 0 && self.get_line_length(next_line_index) > 0 {
+            self.lines[line_index].insert(' ', join_index);
+        }
+        self.merge(line_index, next_line_index);
+        Some(join_index)
+    }
+
+    //removes a leading tab, or up to the given number of leading spaces; returns how many
+    //graphemes were removed
+    pub fn dedent_line(&mut self, line_index: usize, width: usize) -> usize {
+        let Some(line) = self.lines.get(line_index) else {
+            return 0;
+        };
+        let text = line.line_to_string();
+        let len = if text.starts_with('\t') {
+            1
+        } else {
+            text.chars().take(width).take_while(|&c| c == ' ').count()
+        };
+        if len > 0 {
+            self.delete_range(
+                Location {
+                    grapheme_index: 0,
+                    line_index,
+                },
+                Location {
+                    grapheme_index: len,
+                    line_index,
+                },
+            );
+        }
+        len
+    }
+
+    //sets the indentation to the one the lines use, keeping the configured one for what they
+    //do not show: tabs if more lines start with a tab than with spaces, otherwise spaces in
+    //the step most common between a line and a more indented next line
+    pub fn detect_indentation(&mut self, configured: Indentation) {
+        let mut tab_lines: usize = 0;
+        let mut space_lines: usize = 0;
+        let mut step_counts = [0_usize; MAX_DETECTED_INDENT_WIDTH + 1];
+        let mut previous_spaces = 0;
+        for line in self.lines.iter().take(INDENTATION_SEARCH_LINES) {
+            let text = line.line_to_string();
+            if text.trim().is_empty() {
+                continue;
+            }
+            if text.starts_with('\t') {
+                tab_lines = tab_lines.saturating_add(1);
+                continue;
+            }
+            let spaces = text
+                .len()
+                .saturating_sub(text.trim_start_matches(' ').len());
+            if spaces > 0 {
+                space_lines = space_lines.saturating_add(1);
+            }
+            //steps of a single space are more likely alignment than indentation
+            if let Some(count) = step_counts
+                .get_mut(spaces.saturating_sub(previous_spaces))
+                .filter(|_| spaces > previous_spaces.saturating_add(1))
+            {
+                *count = count.saturating_add(1);
+            }
+            previous_spaces = spaces;
+        }
+        self.indentation = if tab_lines > space_lines {
+            Indentation {
+                expand_tabs: false,
+                ..configured
+            }
+        } else if space_lines > 0 {
+            Indentation {
+                expand_tabs: true,
+                width: (0..step_counts.len())
+                    .filter(|&width| step_counts[width] > 0)
+                    .max_by_key(|&width| step_counts[width])
+                    .unwrap_or(configured.width),
+            }
+        } else {
+            configured
+        };
+    }
+
+    //removes the text from start up to, but not including, end
+    pub fn delete_range(&mut self, start: Location, end: Location) {
+        let end_line_index = min(end.line_index, self.lines.len().saturating_sub(1));
+        if start.line_index > end_line_index {
+            return;
+        }
+        let end_grapheme_index = if end.line_index > end_line_index {
+            self.get_line_length(end_line_index)
+        } else {
+            end.grapheme_index
+        };
+        let tail = self.lines[end_line_index].split(end_grapheme_index);
+        self.lines
+            .drain(start.line_index.saturating_add(1)..=end_line_index);
+        if let Some(line) = self.lines.get_mut(start.line_index) {
+            line.split(start.grapheme_index);
+            line.append(&tail);
+        }
+        self.mark_modified(start.line_index);
     }
 
     pub fn merge(&mut self, line_index: usize, merge_to_index: usize) {
         let removed_line = self.lines.remove(merge_to_index);
-        match self.lines.get_mut(line_index) {
-            Some(line) => {
-                line.append(&removed_line);
-                self.is_modified = true;
-            }
-            None => (),
+        if let Some(line) = self.lines.get_mut(line_index) {
+            line.append(&removed_line);
+            self.mark_modified(line_index);
         }
     }
+
+    fn mark_modified(&mut self, line_index: usize) {
+        self.is_modified = true;
+        self.highlighter.invalidate_from(line_index);
+    }
+
+    //highlights the lines which have not been highlighted since they were last edited,
+    //up to the given index
+    pub fn highlight(&mut self, up_to: usize) {
+        self.highlighter.highlight(&self.lines, up_to);
+    }
+
+    pub fn get_highlights(&self, line_index: usize) -> &[Span] {
+        self.highlighter.get_spans(line_index)
+    }
 }
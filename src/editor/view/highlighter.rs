@@ -0,0 +1,331 @@
+use super::line::Line;
+use super::{Style, Syntax, Theme};
+use std::cmp::min;
+use std::ops::Range;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Type,
+    String,
+    Comment,
+    Number,
+}
+
+impl TokenKind {
+    pub const fn style(self, theme: &Theme) -> Style {
+        match self {
+            Self::Keyword => theme.keyword,
+            Self::Type => theme.type_name,
+            Self::String => theme.string,
+            Self::Comment => theme.comment,
+            Self::Number => theme.number,
+        }
+    }
+}
+
+//a highlighted range of graphemes within a line
+pub struct Span {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+//what is still open at the end of a line
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Normal,
+    BlockComment(usize),
+    String(char),
+    RawString(usize),
+}
+
+struct HighlightedLine {
+    spans: Vec<Span>,
+    end_state: State,
+}
+
+//splits lines into highlighted spans, carrying open comments and strings from one line to the
+//next; lines are highlighted when first shown and again only after an edit at or above them
+#[derive(Default)]
+pub struct Highlighter {
+    syntax: Option<Rc<Syntax>>,
+    lines: Vec<HighlightedLine>,
+}
+
+impl Highlighter {
+    pub fn new(syntax: Option<Rc<Syntax>>) -> Self {
+        Self {
+            syntax,
+            lines: Vec::new(),
+        }
+    }
+
+    //forgets the highlights from the given line on, as their state may have changed
+    pub fn invalidate_from(&mut self, line_index: usize) {
+        self.lines.truncate(line_index);
+    }
+
+    //highlights the lines up to, but not including, the given index
+    pub fn highlight(&mut self, lines: &[Line], up_to: usize) {
+        let Some(syntax) = &self.syntax else {
+            return;
+        };
+        let end = min(up_to, lines.len());
+        while self.lines.len() < end {
+            let state = self
+                .lines
+                .last()
+                .map_or(State::Normal, |line| line.end_state);
+            let highlighted = Self::highlight_line(syntax, &lines[self.lines.len()], state);
+            self.lines.push(highlighted);
+        }
+    }
+
+    pub fn get_spans(&self, line_index: usize) -> &[Span] {
+        self.lines
+            .get(line_index)
+            .map_or(&[], |line| line.spans.as_slice())
+    }
+
+    fn highlight_line(syntax: &Syntax, line: &Line, state: State) -> HighlightedLine {
+        let text = line.line_to_string();
+        let mut state = state;
+        let mut byte_spans = Vec::new();
+        let mut pos = 0;
+        while pos < text.len() {
+            let start = pos;
+            let rest = &text[pos..];
+            if state == State::Normal {
+                if syntax
+                    .line_comment
+                    .as_ref()
+                    .is_some_and(|comment| rest.starts_with(comment.as_str()))
+                {
+                    byte_spans.push((start..text.len(), TokenKind::Comment));
+                    break;
+                }
+                if let Some((len, opened)) = Self::match_opening(syntax, rest) {
+                    pos = pos.saturating_add(len);
+                    state = opened;
+                } else {
+                    let (len, kind) = Self::match_token(syntax, rest);
+                    pos = pos.saturating_add(len);
+                    if let Some(kind) = kind {
+                        byte_spans.push((start..pos, kind));
+                    }
+                    continue;
+                }
+            }
+            let (end, next_state) = Self::scan_open(syntax, &text, pos, state);
+            let kind = match state {
+                State::BlockComment(_) => TokenKind::Comment,
+                _ => TokenKind::String,
+            };
+            byte_spans.push((start..end, kind));
+            pos = end;
+            state = next_state;
+        }
+        if let State::String(_) = state {
+            if !syntax.multiline_strings {
+                state = State::Normal;
+            }
+        }
+        //spans are found on bytes, but rendered on graphemes
+        let offsets: Vec<usize> = text
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .collect();
+        let to_grapheme = |byte: usize| offsets.partition_point(|&offset| offset < byte);
+        HighlightedLine {
+            spans: byte_spans
+                .into_iter()
+                .map(|(range, kind)| Span {
+                    range: to_grapheme(range.start)..to_grapheme(range.end),
+                    kind,
+                })
+                .collect(),
+            end_state: state,
+        }
+    }
+
+    //returns the length of a comment or string opening at the start of the text,
+    //and the state it opens
+    fn match_opening(syntax: &Syntax, text: &str) -> Option<(usize, State)> {
+        if let Some((open, _)) = &syntax.block_comment {
+            if text.starts_with(open.as_str()) {
+                return Some((open.len(), State::BlockComment(1)));
+            }
+        }
+        if syntax.raw_strings {
+            let prefix = text.strip_prefix('b').unwrap_or(text);
+            if let Some(hashed) = prefix.strip_prefix('r') {
+                let hashes = hashed.chars().take_while(|&c| c == '#').count();
+                if hashed[hashes..].starts_with('"') {
+                    let len = text
+                        .len()
+                        .saturating_sub(hashed.len())
+                        .saturating_add(hashes)
+                        .saturating_add(1);
+                    return Some((len, State::RawString(hashes)));
+                }
+            }
+        }
+        let c = text.chars().next()?;
+        syntax
+            .string_delimiters
+            .contains(&c)
+            .then_some((c.len_utf8(), State::String(c)))
+    }
+
+    //returns the length of the word, number or character at the start of the text, and how
+    //to highlight it
+    fn match_token(syntax: &Syntax, text: &str) -> (usize, Option<TokenKind>) {
+        let Some(first) = text.chars().next() else {
+            return (0, None);
+        };
+        if first.is_ascii_digit() {
+            let mut len: usize = 0;
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                let is_decimal_point = c == '.' && chars.peek().is_some_and(char::is_ascii_digit);
+                if !(c.is_alphanumeric() || c == '_' || is_decimal_point) {
+                    break;
+                }
+                len = len.saturating_add(c.len_utf8());
+            }
+            return (len, Some(TokenKind::Number));
+        }
+        if first.is_alphabetic() || first == '_' {
+            let len = text
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(text.len());
+            let word = &text[..len];
+            let kind = if syntax.keywords.iter().any(|keyword| keyword == word) {
+                Some(TokenKind::Keyword)
+            } else if syntax.types.iter().any(|name| name == word)
+                || (syntax.capitalized_types && first.is_uppercase())
+            {
+                Some(TokenKind::Type)
+            } else {
+                None
+            };
+            return (len, kind);
+        }
+        if first == '\'' && syntax.char_literals {
+            if let Some(len) = Self::match_char_literal(text) {
+                return (len, Some(TokenKind::String));
+            }
+        }
+        (first.len_utf8(), None)
+    }
+
+    //matches 'c' and escapes like '\n' or '\u{1F600}'
+    fn match_char_literal(text: &str) -> Option<usize> {
+        let mut chars = text.char_indices().skip(1);
+        let (_, c) = chars.next()?;
+        if c == '\\' {
+            //the escaped character may itself be a quote, so the closing one is looked for after it
+            let (index, escaped) = chars.next()?;
+            let rest = index.saturating_add(escaped.len_utf8());
+            return text
+                .get(rest..)?
+                .find('\'')
+                .map(|index| index.saturating_add(rest).saturating_add(1));
+        }
+        match chars.next()? {
+            (index, '\'') => Some(index.saturating_add(1)),
+            _ => None,
+        }
+    }
+
+    //finds where the comment or string open at the given position ends, and the state after it
+    fn scan_open(syntax: &Syntax, text: &str, from: usize, state: State) -> (usize, State) {
+        let mut state = state;
+        let mut pos = from;
+        while pos < text.len() {
+            let rest = &text[pos..];
+            let mut len = rest.chars().next().map_or(1, char::len_utf8);
+            match state {
+                State::BlockComment(depth) => {
+                    if let Some((open, close)) = &syntax.block_comment {
+                        if rest.starts_with(close.as_str()) {
+                            len = close.len();
+                            state = match depth.saturating_sub(1) {
+                                0 => State::Normal,
+                                depth => State::BlockComment(depth),
+                            };
+                        } else if syntax.nested_comments && rest.starts_with(open.as_str()) {
+                            len = open.len();
+                            state = State::BlockComment(depth.saturating_add(1));
+                        }
+                    }
+                }
+                State::String(delimiter) => {
+                    if rest.starts_with('\\') {
+                        len = rest.chars().take(2).map(char::len_utf8).sum();
+                    } else if rest.starts_with(delimiter) {
+                        state = State::Normal;
+                    }
+                }
+                State::RawString(hashes) => {
+                    if rest.starts_with('"')
+                        && rest[1..].chars().take_while(|&c| c == '#').count() >= hashes
+                    {
+                        len = hashes.saturating_add(1);
+                        state = State::Normal;
+                    }
+                }
+                State::Normal => {}
+            }
+            pos = pos.saturating_add(len);
+            if state == State::Normal {
+                break;
+            }
+        }
+        (pos, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_spans(text: &str) -> Vec<Range<usize>> {
+        let mut highlighter = Highlighter::new(Some(Rc::new(Syntax::rust())));
+        highlighter.highlight(&[Line::from(text)], 1);
+        highlighter
+            .get_spans(0)
+            .iter()
+            .filter(|span| span.kind == TokenKind::String)
+            .map(|span| span.range.clone())
+            .collect()
+    }
+
+    #[test]
+    fn char_literal_with_escaped_quote() {
+        assert_eq!(string_spans("let c = '\\'';"), vec![8..12]);
+    }
+
+    #[test]
+    fn char_literal_with_escaped_backslash() {
+        assert_eq!(string_spans("let c = '\\\\';"), vec![8..12]);
+    }
+
+    #[test]
+    fn char_literal_with_escaped_letter() {
+        assert_eq!(string_spans("let c = '\\n';"), vec![8..12]);
+    }
+
+    #[test]
+    fn char_literal_with_unicode_escape() {
+        assert_eq!(string_spans("let c = '\\u{1F600}';"), vec![8..19]);
+    }
+
+    #[test]
+    fn lifetime_is_not_a_char_literal() {
+        assert!(string_spans("fn f<'a>(s: &'a str) {}").is_empty());
+    }
+}
@@ -1,8 +1,11 @@
+use super::highlighter::Span;
+use super::{Terminal, Theme};
+use std::cmp::{max, min};
 use std::ops::Range;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Line {
     fragments: Vec<TextFragment>,
 }
@@ -11,17 +14,26 @@ pub struct Line {
 enum GraphemeWidth {
     Half,
     Full,
+    //reaches the next tab stop, so its width depends on the column it starts at
+    Tab,
 }
 
 impl GraphemeWidth {
-    const fn saturating_add(self, other: usize) -> usize {
+    //returns the column after a grapheme of this width starting at the given column
+    fn saturating_add(self, col: usize, tab_width: usize) -> usize {
         match self {
-            Self::Half => other.saturating_add(1),
-            Self::Full => other.saturating_add(2),
+            Self::Half => col.saturating_add(1),
+            Self::Full => col.saturating_add(2),
+            Self::Tab => {
+                let tab_width = tab_width.max(1);
+                let offset = col.checked_rem(tab_width).unwrap_or(0);
+                col.saturating_add(tab_width.saturating_sub(offset))
+            }
         }
     }
 }
 
+#[derive(Clone)]
 struct TextFragment {
     grapheme: String,
     rendered_width: GraphemeWidth,
@@ -38,8 +50,10 @@ impl Line {
         let fragments = line_str
             .graphemes(true)
             .map(|grapheme| {
-                let (replacement, rendered_width) = Self::replacement_character(grapheme)
-                    .map_or_else(
+                let (replacement, rendered_width) = if grapheme == "\t" {
+                    (None, GraphemeWidth::Tab)
+                } else {
+                    Self::replacement_character(grapheme).map_or_else(
                         || {
                             let unicode_width = grapheme.width();
                             match unicode_width {
@@ -48,7 +62,8 @@ impl Line {
                             }
                         },
                         |c| (Some(c), GraphemeWidth::Half),
-                    );
+                    )
+                };
                 TextFragment {
                     grapheme: grapheme.to_string(),
                     rendered_width,
@@ -87,7 +102,6 @@ impl Line {
         let width = string.width();
         match string {
             " " => None,
-            "\t" => Some(' '),
             _ if width > 0 && string.trim().is_empty() => Some('␣'),
             _ if width == 0 => {
                 let mut chars = string.chars();
@@ -106,7 +120,16 @@ impl Line {
         self.fragments.len()
     }
 
-    pub fn get_visible_graphemes(&self, range: Range<usize>) -> String {
+    //renders the graphemes within the given columns in the theme's styles for their highlights
+    //and the selection
+    pub fn get_visible_graphemes(
+        &self,
+        range: Range<usize>,
+        highlights: &[Span],
+        selection: Option<&Range<usize>>,
+        theme: &Theme,
+        tab_width: usize,
+    ) -> String {
         let start = range.start;
         let end = range.end;
         if start >= end {
@@ -114,13 +137,31 @@ impl Line {
         }
         let mut result = String::new();
         let mut current_pos = 0;
-        for fragment in &self.fragments {
-            let fragment_end = fragment.rendered_width.saturating_add(current_pos);
+        let mut current_style = theme.text;
+        for (index, fragment) in self.fragments.iter().enumerate() {
+            let fragment_end = fragment
+                .rendered_width
+                .saturating_add(current_pos, tab_width);
             if current_pos >= end {
                 break;
             }
             if fragment_end > start {
-                if fragment_end > end || current_pos < start {
+                let mut style = highlights
+                    .iter()
+                    .find(|span| span.range.contains(&index))
+                    .map_or(theme.text, |span| span.kind.style(theme).over(theme.text));
+                if selection.is_some_and(|selection| selection.contains(&index)) {
+                    style = theme.selection.over(style);
+                }
+                if style != current_style {
+                    result.push_str(&Terminal::style_sequence(&style));
+                    current_style = style;
+                }
+                if let GraphemeWidth::Tab = fragment.rendered_width {
+                    let visible_width =
+                        min(fragment_end, end).saturating_sub(max(current_pos, start));
+                    result.push_str(&" ".repeat(visible_width));
+                } else if fragment_end > end || current_pos < start {
                     result.push('⋯');
                 } else if let Some(char) = fragment.replacement {
                     result.push(char);
@@ -130,26 +171,145 @@ impl Line {
             }
             current_pos = fragment_end;
         }
-        return result;
+        if current_style != theme.text {
+            result.push_str(&Terminal::style_sequence(&theme.text));
+        }
+        result
     }
 
-    pub fn get_previous_width(&self, grapheme_index: usize) -> usize {
+    //returns the column at which the given grapheme starts
+    pub fn get_previous_width(&self, grapheme_index: usize, tab_width: usize) -> usize {
         self.fragments
             .iter()
             .take(grapheme_index)
-            .map(|fragment| match fragment.rendered_width {
-                GraphemeWidth::Half => 1,
-                GraphemeWidth::Full => 2,
+            .fold(0, |col, fragment| {
+                fragment.rendered_width.saturating_add(col, tab_width)
+            })
+    }
+
+    //returns the graphemes starting each row when the line is wrapped to the given width;
+    //rows break after whitespace where possible, and whitespace may hang past the width
+    pub fn get_wrap_starts(&self, width: usize, tab_width: usize) -> Vec<usize> {
+        let mut starts = vec![0];
+        let mut row_start = 0;
+        let mut row_start_col = 0;
+        let mut col = 0;
+        let mut break_index = None;
+        for (index, fragment) in self.fragments.iter().enumerate() {
+            let is_blank = fragment.grapheme.trim().is_empty();
+            let fragment_end = fragment.rendered_width.saturating_add(col, tab_width);
+            if !is_blank && fragment_end.saturating_sub(row_start_col) > width && index > row_start
+            {
+                row_start = break_index
+                    .filter(|&break_index| break_index > row_start)
+                    .unwrap_or(index);
+                starts.push(row_start);
+                row_start_col = self.get_previous_width(row_start, tab_width);
+                break_index = None;
+            }
+            col = fragment_end;
+            if is_blank {
+                break_index = Some(index.saturating_add(1));
+            }
+        }
+        starts
+    }
+
+    //returns the number of leading whitespace graphemes, including tabs and non-ASCII spaces
+    pub fn get_indentation_len(&self) -> usize {
+        self.fragments
+            .iter()
+            .take_while(|fragment| fragment.grapheme.trim().is_empty())
+            .count()
+    }
+
+    //returns the index at which the line's trailing whitespace starts
+    pub fn get_trailing_whitespace_start(&self) -> usize {
+        let trailing_len = self
+            .fragments
+            .iter()
+            .rev()
+            .take_while(|fragment| fragment.grapheme.trim().is_empty())
+            .count();
+        self.graphemes_len().saturating_sub(trailing_len)
+    }
+
+    //returns the index of the grapheme rendered at the given column, or the line's length past its end
+    pub fn get_grapheme_index_at(&self, col: usize, tab_width: usize) -> usize {
+        let mut current_pos = 0;
+        for (index, fragment) in self.fragments.iter().enumerate() {
+            current_pos = fragment
+                .rendered_width
+                .saturating_add(current_pos, tab_width);
+            if col < current_pos {
+                return index;
+            }
+        }
+        self.graphemes_len()
+    }
+
+    //returns the grapheme indices at which unicode word segments start, followed by the line's length
+    pub fn get_word_boundaries(&self) -> Vec<usize> {
+        let text = self.line_to_string();
+        let mut boundaries = Vec::new();
+        let mut byte_index = 0;
+        let mut grapheme_index = 0;
+        for (start, _) in text.split_word_bound_indices() {
+            while byte_index < start {
+                let Some(fragment) = self.fragments.get(grapheme_index) else {
+                    break;
+                };
+                byte_index = byte_index.saturating_add(fragment.grapheme.len());
+                grapheme_index = grapheme_index.saturating_add(1);
+            }
+            boundaries.push(grapheme_index);
+        }
+        boundaries.push(self.graphemes_len());
+        boundaries
+    }
+
+    //returns the word segments which contain more than whitespace
+    fn get_words(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let boundaries = self.get_word_boundaries();
+        (1..boundaries.len())
+            .map(move |index| boundaries[index.saturating_sub(1)]..boundaries[index])
+            .filter(|range| {
+                self.fragments[range.clone()]
+                    .iter()
+                    .any(|fragment| !fragment.grapheme.trim().is_empty())
             })
-            .sum()
+    }
+
+    //returns the start of the word before the given grapheme, or 0 if there is none
+    pub fn get_previous_word_start(&self, grapheme_index: usize) -> usize {
+        self.get_words()
+            .filter(|word| word.start < grapheme_index)
+            .last()
+            .map_or(0, |word| word.start)
+    }
+
+    //returns the end of the word after the given grapheme, or the line's length if there is none
+    pub fn get_next_word_end(&self, grapheme_index: usize) -> usize {
+        self.get_words()
+            .find(|word| word.end > grapheme_index)
+            .map_or(self.graphemes_len(), |word| word.end)
+    }
+
+    //returns the graphemes of the word segment around the given grapheme
+    pub fn get_word_range_at(&self, grapheme_index: usize) -> Range<usize> {
+        let boundaries = self.get_word_boundaries();
+        boundaries
+            .windows(2)
+            .find(|pair| pair[0] <= grapheme_index && grapheme_index < pair[1])
+            .map_or(grapheme_index..grapheme_index, |pair| pair[0]..pair[1])
     }
 
     pub fn append(&mut self, other: &Self) {
         let mut merged_line = String::new();
-        for (_, fragment) in self.fragments.iter().enumerate() {
+        for fragment in &self.fragments {
             merged_line.push_str(&fragment.grapheme);
         }
-        for (_, fragment) in other.fragments.iter().enumerate() {
+        for fragment in &other.fragments {
             merged_line.push_str(&fragment.grapheme);
         }
         self.fragments = Self::str_to_fragments(&merged_line);
@@ -167,7 +327,7 @@ impl Line {
 
     pub fn line_to_string(&self) -> String {
         let mut result = String::new();
-        for (_, fragment) in self.fragments.iter().enumerate() {
+        for fragment in &self.fragments {
             result.push_str(&fragment.grapheme);
         }
         result
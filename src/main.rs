@@ -7,8 +7,37 @@
     clippy::integer_division
 )]
 mod editor;
-use editor::Editor;
+use editor::arguments::{self, Action, Arguments};
+use editor::{Editor, NAME};
+use std::env;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
 
-fn main() {
-    Editor::new().unwrap().run();
+fn main() -> ExitCode {
+    //usage errors are reported before the terminal enters raw mode
+    let arguments = match Arguments::parse(env::args().skip(1)) {
+        Ok(Action::Edit(arguments)) => arguments,
+        Ok(Action::Help) => {
+            let _ = writeln!(stdout(), "{}", arguments::usage());
+            return ExitCode::SUCCESS;
+        }
+        Ok(Action::Version) => {
+            let _ = writeln!(stdout(), "{}", arguments::version());
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{NAME}: {err}\n\n{}", arguments::usage());
+            return ExitCode::from(2);
+        }
+    };
+    match Editor::new(arguments) {
+        Ok(mut editor) => {
+            editor.run();
+            editor.exit_code()
+        }
+        Err(err) => {
+            eprintln!("{NAME}: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }